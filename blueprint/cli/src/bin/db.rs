@@ -1,24 +1,58 @@
+{%- if database == "sqlite" -%}
+{%- assign database_type = "Sqlite" -%}
+{%- assign type_prefix = "Sqlite" -%}
+{%- elsif database == "mysql" -%}
+{%- assign database_type = "MySql" -%}
+{%- assign type_prefix = "MySql" -%}
+{%- else -%}
+{%- assign database_type = "Postgres" -%}
+{%- assign type_prefix = "Pg" -%}
+{%- endif -%}
 use anyhow::{anyhow, Context};
 use clap::{Parser, Subcommand};
 use {{crate_name}}_cli::util::ui::UI;
 use {{crate_name}}_config::DatabaseConfig;
 use {{crate_name}}_config::{load_config, parse_env, Config, Environment};
 use guppy::{Version, VersionReq};
-use sqlx::postgres::{PgConnectOptions, PgConnection};
+{% if database == "mysql" -%}
+use regex::Regex;
+{% endif -%}
+use sqlx::{{database}}::{ {{type_prefix}}ConnectOptions, {{type_prefix}}Connection, {{type_prefix}}Row};
 use sqlx::{
-    migrate::{AppliedMigration, Migrate, Migration, MigrationType, Migrator},
-    ConnectOptions, Connection, Executor,
+    migrate::{AppliedMigration, Migrate, {% unless database == "postgres" %}MigrateDatabase, {% endunless %}Migration, MigrationType, Migrator},
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc},
+    types::Uuid,
+    Column, ConnectOptions, Connection, Executor, Row, TypeInfo,{% unless database == "postgres" %} {{database_type}},{% endunless %}
 };
 use tokio::io::{stdin, AsyncBufReadExt};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
 use std::process::{ExitCode, Stdio};
+{% unless database == "sqlite" -%}
+use std::time::Duration;
+use tokio::time::Instant;
+{% endunless -%}
 use url::Url;
 
 /// The version of sqlx-cli required
 const SQLX_CLI_VERSION: &str = "0.8";
 
+/// The file the database schema is dumped to, relative to the project root
+const SCHEMA_FILE: &str = "db/schema.sql";
+
+/// The database's interactive client that `cargo db console` starts if it is installed
+const CONSOLE_CLIENT: &str = "{% if database == "sqlite" %}sqlite3{% elsif database == "mysql" %}mysql{% else %}psql{% endif %}";
+{%- unless database == "sqlite" %}
+
+/// The port the database server listens on if the database URL doesn't specify one
+const DEFAULT_PORT: u16 = {% if database == "mysql" %}3306{% else %}5432{% endif %};
+
+/// How long to wait between attempts to connect to the database server when passing `--wait`
+const WAIT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+{%- endunless %}
+
 #[tokio::main]
 async fn main() -> ExitCode {
     let mut stdout = std::io::stdout();
@@ -51,6 +85,14 @@ struct Cli {
 
     #[arg(long, global = true, help = "Disable debug output.")]
     quiet: bool,
+
+    #[arg(long, global = true, help = "Allow destructive commands against protected databases after confirming the database name.")]
+    force: bool,
+{%- unless database == "sqlite" %}
+
+    #[arg(long, global = true, help = "Wait up to this long for the database server to accept connections, e.g. 30s or 2m.", value_parser = parse_wait)]
+    wait: Option<Duration>,
+{%- endunless %}
 }
 
 #[derive(Subcommand)]
@@ -60,31 +102,80 @@ enum Commands {
     #[command(about = "Create the database")]
     Create,
     #[command(about = "Migrate the database")]
-    Migrate,
+    Migrate {
+        #[arg(long, help = "Apply pending migrations even if applied migrations were changed locally.")]
+        allow_drift: bool,
+
+        #[arg(long, help = "Print the SQL of all pending migrations without applying them.")]
+        dry_run: bool,
+
+        #[arg(long, help = "Apply all pending migrations in a single transaction, rolling back all of them if one fails.", conflicts_with = "dry_run")]
+        atomic: bool,
+
+        #[arg(short, long, help = "Number of pending migrations to apply.", conflicts_with = "to")]
+        steps: Option<u32>,
+
+        #[arg(short, long, help = "Migrate up to (and including) the migration with this name or version.")]
+        to: Option<String>,
+    },
+    #[command(about = "Show the status of all migrations")]
+    Status,
     #[command(about = "Rollback database migrations")]
     Rollback {
         #[arg(short, long, default_value = "1", help = "Number of migrations to roll back.", conflicts_with = "to")]
         steps: u32,
 
-        #[arg(short, long, help = "Roll back up to (but not including) the migration with this name.")]
+        #[arg(short, long, help = "Roll back up to (but not including) the migration with this name or version.")]
         to: Option<String>,
     },
+    #[command(about = "Roll back and reapply the latest migrations, verifying that rolling back restores the schema")]
+    Redo {
+        #[arg(short, long, default_value = "1", help = "Number of migrations to redo.")]
+        steps: u32,
+    },
+    #[command(about = "Dump or load the database schema")]
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
     #[command(about = "Reset (drop, create, migrate) the database")]
     Reset,
     #[command(about = "Seed the database")]
-    Seed,
+    Seed {
+        #[arg(short, long, help = "Number of fake records to generate per entity after running the SQL seeds.")]
+        count: Option<usize>,
+    },
+    #[command(about = "Open an interactive SQL console for the database")]
+    Console,
+    #[command(about = "Execute SQL against the database and print the results")]
+    Exec {
+        #[arg(help = "The SQL to execute, e.g. \"SELECT * FROM users\".")]
+        sql: String,
+    },
     #[command(about = "Generate query metadata to support offline compile-time verification")]
     Prepare,
 }
 
+#[derive(Subcommand)]
+enum SchemaCommands {
+    #[command(about = "Write the database schema to db/schema.sql")]
+    Dump,
+    #[command(about = "Recreate the database from db/schema.sql")]
+    Load,
+}
+
 #[allow(missing_docs)]
 async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
     let config: Result<Config, anyhow::Error> = load_config(&cli.env);
     match config {
         Ok(config) => {
             let migrations_path = db_package_root()?.join("migrations");
+            if !matches!(cli.command, Commands::Prepare) {
+                ensure_database_reachable(ui, &cli, &config.database).await?;
+            }
             match cli.command {
                 Commands::Drop => {
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
                     ui.info(&format!("Dropping {} database…", &cli.env));
                     let db_name = drop(&config.database)
                         .await
@@ -100,18 +191,56 @@ async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
                     ui.success(&format!("Created database {db_name} successfully."));
                     Ok(())
                 }
-                Commands::Migrate => {
-                    ui.info(&format!("Migrating {} database…", &cli.env));
+                Commands::Migrate { allow_drift, dry_run, atomic, steps, to } => {
+                    if dry_run {
+                        ui.info(&format!("Pending migrations of {} database:", &cli.env));
+                    } else if let Some(ref name) = to {
+                        ui.info(&format!("Migrating {} database to \"{name}\"…", &cli.env));
+                    } else if let Some(steps) = steps {
+                        ui.info(&format!("Migrating {} database ({steps} step(s))…", &cli.env));
+                    } else {
+                        ui.info(&format!("Migrating {} database…", &cli.env));
+                    }
+                    let options = MigrateOptions { allow_drift, dry_run, atomic, steps, to };
                     ui.indent();
-                    let migrations = migrate(ui, &config.database, &migrations_path)
+                    let migrations = migrate(ui, &config.database, &migrations_path, &options)
                         .await
                         .context("Could not migrate database!");
                     ui.outdent();
                     let migrations = migrations?;
-                    ui.success(&format!("{migrations} migrations applied."));
+                    if !dry_run {
+                        update_schema_file(ui, &cli.env, &config.database).await;
+                    }
+                    if dry_run {
+                        ui.success(&format!("{migrations} migration(s) pending – nothing was applied."));
+                    } else {
+                        ui.success(&format!("{migrations} migrations applied."));
+                    }
+                    Ok(())
+                }
+                Commands::Status => {
+                    ui.info(&format!("Status of {} database migrations:", &cli.env));
+                    let statuses = status(&config.database, &migrations_path)
+                        .await
+                        .context("Could not determine the migration status!")?;
+                    for line in format_status_table(&statuses) {
+                        ui.log(&line);
+                    }
+
+                    let mismatched = statuses.iter().filter(|s| s.checksum_mismatch).count();
+                    if mismatched > 0 {
+                        ui.warning(&format!("{mismatched} applied migration(s) were changed locally after they were applied."));
+                    }
+                    let missing = statuses.iter().filter(|s| s.state == MigrationState::Missing).count();
+                    if missing > 0 {
+                        return Err(anyhow!("{missing} applied migration(s) are missing locally!"));
+                    }
+                    let pending = statuses.iter().filter(|s| s.state == MigrationState::Pending).count();
+                    ui.success(&format!("{} migration(s) applied, {pending} pending.", statuses.len() - pending));
                     Ok(())
                 }
                 Commands::Rollback { steps, to } => {
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
                     if let Some(ref name) = to {
                         ui.info(&format!("Rolling back {} database to \"{name}\"…", &cli.env));
                     } else {
@@ -123,18 +252,42 @@ async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
                         .context("Could not rollback database!");
                     ui.outdent();
                     let reverted = result?;
-                    ui.success(&format!("{reverted} migration(s) reverted."));
+                    update_schema_file(ui, &cli.env, &config.database).await;
+                    ui.success(&format!("{} migration(s) reverted.", reverted.len()));
+                    Ok(())
+                }
+                Commands::Redo { steps } => {
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
+                    ui.info(&format!("Redoing {} database migrations ({steps} step(s))…", &cli.env));
+                    ui.indent();
+                    let result = redo(ui, &config.database, &migrations_path, steps)
+                        .await
+                        .context("Could not redo migrations!");
+                    ui.outdent();
+                    let redone = result?;
+                    update_schema_file(ui, &cli.env, &config.database).await;
+                    ui.success(&format!("{redone} migration(s) redone."));
                     Ok(())
                 }
-                Commands::Seed => {
+                Commands::Seed { count } => {
                     ui.info(&format!("Seeding {} database…", &cli.env));
-                    seed(&config.database)
+                    ui.indent();
+                    let result = seed(ui, &cli.env, &config.database)
                         .await
-                        .context("Could not seed database!")?;
+                        .context("Could not seed database!");
+                    ui.outdent();
+                    result?;
+                    if let Some(count) = count {
+                        ui.info(&format!("Generating {count} fake record(s) per entity…"));
+                        generate_seeds(&cli.env, count, cli.no_color, cli.quiet)
+                            .await
+                            .context("Could not generate seeds!")?;
+                    }
                     ui.success("Seeded database successfully.");
                     Ok(())
                 }
                 Commands::Reset => {
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
                     ui.info(&format!("Resetting {} database…", &cli.env));
                     ui.indent();
                     let result = reset(ui, &config.database, &migrations_path)
@@ -142,9 +295,48 @@ async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
                         .context("Could not reset the database!");
                     ui.outdent();
                     let db_name = result?;
+                    update_schema_file(ui, &cli.env, &config.database).await;
                     ui.success(&format!("Reset database {db_name} successfully."));
                     Ok(())
                 }
+                Commands::Schema { command: SchemaCommands::Dump } => {
+                    ui.info(&format!("Dumping {} database schema…", &cli.env));
+                    write_schema_file(&config.database)
+                        .await
+                        .context("Could not dump the schema!")?;
+                    ui.success(&format!("Wrote {SCHEMA_FILE} successfully."));
+                    Ok(())
+                }
+                Commands::Schema { command: SchemaCommands::Load } => {
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
+                    ui.info(&format!("Loading {SCHEMA_FILE} into {} database…", &cli.env));
+                    ui.indent();
+                    let result = load_schema(ui, &config.database)
+                        .await
+                        .context("Could not load the schema!");
+                    ui.outdent();
+                    let db_name = result?;
+                    ui.success(&format!("Loaded schema into database {db_name} successfully."));
+                    Ok(())
+                }
+                Commands::Console => {
+                    ui.info(&format!("Opening console for {} database…", &cli.env));
+                    console(ui, &config.database)
+                        .await
+                        .context("Could not open the database console!")
+                }
+                Commands::Exec { sql } => {
+                    // the SQL might change or delete data so it is treated like the destructive commands
+                    ensure_unprotected_or_confirmed(ui, &cli.env, &config.database, cli.force).await?;
+                    let mut connection = get_db_client(&config.database).await?;
+                    let lines = execute_sql(&mut connection, &sql)
+                        .await
+                        .context("Could not execute SQL!")?;
+                    for line in lines {
+                        ui.log(&line);
+                    }
+                    Ok(())
+                }
                 Commands::Prepare => {
                     if let Err(e) = ensure_sqlx_cli_installed(ui).await {
                         return Err(e.context("Error ensuring sqlx-cli is installed!"));
@@ -184,28 +376,23 @@ async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
     }
 }
 
+{% if database == "postgres" -%}
 async fn drop(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
-    let db_config = get_db_config(config);
-    let db_name = db_config
-        .get_database()
-        .context("Failed to get database name!")?;
-    let mut root_connection = get_root_db_client(config).await;
+    let db_name = get_db_name(config)?;
+    let mut root_connection = get_root_db_client(config).await?;
 
-    let query = format!("DROP DATABASE {db_name}");
+    let query = format!("DROP DATABASE IF EXISTS {db_name}");
     root_connection
         .execute(query.as_str())
         .await
         .context("Failed to drop database!")?;
 
-    Ok(String::from(db_name))
+    Ok(db_name)
 }
 
 async fn create(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
-    let db_config = get_db_config(config);
-    let db_name = db_config
-        .get_database()
-        .context("Failed to get database name!")?;
-    let mut root_connection = get_root_db_client(config).await;
+    let db_name = get_db_name(config)?;
+    let mut root_connection = get_root_db_client(config).await?;
 
     let query = format!("CREATE DATABASE {db_name}");
     root_connection
@@ -213,9 +400,33 @@ async fn create(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
         .await
         .context("Failed to create database!")?;
 
-    Ok(String::from(db_name))
+    Ok(db_name)
+}
+{%- else -%}
+async fn drop(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = get_db_name(config)?;
+    let exists = {{database_type}}::database_exists(&config.url)
+        .await
+        .context("Failed to check whether the database exists!")?;
+    if exists {
+        {{database_type}}::drop_database(&config.url)
+            .await
+            .context("Failed to drop database!")?;
+    }
+
+    Ok(db_name)
 }
 
+async fn create(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_name = get_db_name(config)?;
+    {{database_type}}::create_database(&config.url)
+        .await
+        .context("Failed to create database!")?;
+
+    Ok(db_name)
+}
+{%- endif %}
+
 struct SqlxMigrator;
 
 impl SqlxMigrator {
@@ -338,6 +549,16 @@ impl SqlxMigrator {
         Ok(migrations)
     }
 
+    /// The path of the file a migration was read from, relative to the migrations directory.
+    fn migration_file(migration: &Migration) -> PathBuf {
+        let name = format!("{}__{}", migration.version, migration.description);
+        match migration.migration_type {
+            MigrationType::Simple => PathBuf::from(name),
+            MigrationType::ReversibleDown => PathBuf::from(name).join("down.sql"),
+            _ => PathBuf::from(name).join("up.sql"),
+        }
+    }
+
     fn extract_meta_from_name(dir: &Path) -> Result<(i64, String), anyhow::Error> {
         let dir_name = dir
             .file_name()
@@ -358,19 +579,23 @@ impl SqlxMigrator {
     }
 }
 
+/// Connects to the database and lists the applied migrations, creating the migrations table if it does not exist – unless `read_only` is set, in which case no migrations are applied if the table does not exist.
 async fn prepare_migrations(
     config: &DatabaseConfig,
-) -> Result<(PgConnection, HashMap<i64, AppliedMigration>), anyhow::Error> {
-    let db_config = get_db_config(config);
-    let mut connection = db_config
-        .connect()
-        .await
-        .context("Failed to connect to database!")?;
+    read_only: bool,
+) -> Result<({{type_prefix}}Connection, HashMap<i64, AppliedMigration>), anyhow::Error> {
+    let mut connection = get_db_client(config).await?;
 
-    connection
-        .ensure_migrations_table()
-        .await
-        .context("Failed to ensure migrations table!")?;
+    if read_only {
+        if !migrations_table_exists(&mut connection).await? {
+            return Ok((connection, HashMap::new()));
+        }
+    } else {
+        connection
+            .ensure_migrations_table()
+            .await
+            .context("Failed to ensure migrations table!")?;
+    }
 
     let applied_migrations: HashMap<_, _> = connection
         .list_applied_migrations()
@@ -383,41 +608,297 @@ async fn prepare_migrations(
     Ok((connection, applied_migrations))
 }
 
-async fn migrate(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path) -> Result<i32, anyhow::Error> {
+async fn migrations_table_exists(connection: &mut {{type_prefix}}Connection) -> Result<bool, anyhow::Error> {
+    {% if database == "sqlite" -%}
+    let query = "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'";
+    {%- elsif database == "mysql" -%}
+    let query = "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = '_sqlx_migrations'";
+    {%- else -%}
+    let query = "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = '_sqlx_migrations'";
+    {%- endif %}
+    let count: i64 = sqlx::query_scalar(query)
+        .fetch_one(&mut *connection)
+        .await
+        .context("Failed to check for the migrations table!")?;
+
+    Ok(count > 0)
+}
+
+#[derive(Default)]
+struct MigrateOptions {
+    /// Apply pending migrations even if applied ones were changed locally.
+    allow_drift: bool,
+    /// Only print the SQL of the pending migrations.
+    dry_run: bool,
+    /// Apply all pending migrations in one transaction.
+    atomic: bool,
+    /// Only apply this number of pending migrations.
+    steps: Option<u32>,
+    /// Only apply pending migrations up to and including the one with this name or version.
+    to: Option<String>,
+}
+
+/// Applies the pending migrations selected by `options` and returns their number – or only prints them in case of a dry run.
+async fn migrate(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path, options: &MigrateOptions) -> Result<i32, anyhow::Error> {
     let migrator = SqlxMigrator::up_migrator(migrations_path)
         .context("Failed to build migrator!")?;
-    let (mut connection, applied_migrations) = prepare_migrations(config).await?;
+    // a dry run must not change the database, not even by creating the migrations table
+    let (mut connection, applied_migrations) = prepare_migrations(config, options.dry_run).await?;
+
+    let drifted = drifted_migrations(&migrator, &applied_migrations);
+    if !drifted.is_empty() {
+        for (migration, applied) in &drifted {
+            ui.warning(&format!(
+                "{} was changed after it was applied (checksum applied: {}, local: {}).",
+                SqlxMigrator::migration_file(migration).display(),
+                short_checksum(&applied.checksum),
+                short_checksum(&migration.checksum),
+            ));
+        }
+        if !options.allow_drift {
+            return Err(anyhow!(
+                "{} applied migration(s) were changed locally! Restore the applied version (see e.g. `git log -p -- db/migrations`) and add a new migration for the changes instead, or pass --allow-drift to migrate anyway.",
+                drifted.len()
+            ));
+        }
+    }
+
+    let target_version = if let Some(ref name) = options.to {
+        let version = find_migration_version(&migrator, name)?;
+        if applied_migrations.contains_key(&version) {
+            return Err(anyhow!("Migration \"{name}\" has already been applied."));
+        }
+
+        Some(version)
+    } else {
+        None
+    };
+
+    let pending: Vec<&Migration> = migrator
+        .iter()
+        .filter(|migration| !applied_migrations.contains_key(&migration.version))
+        .take_while(|migration| target_version.is_none_or(|target| migration.version <= target))
+        .take(options.steps.map_or(usize::MAX, |steps| steps as usize))
+        .collect();
 
-    let mut applied = 0;
-    for migration in migrator.iter() {
-        if !applied_migrations.contains_key(&migration.version) {
+    if options.dry_run {
+        for migration in &pending {
+            ui.log(&format!("-- {}", SqlxMigrator::migration_file(migration).display()));
+            for line in migration.sql.trim_end().lines() {
+                ui.log(line);
+            }
+        }
+        return Ok(pending.len() as i32);
+    }
+
+    if options.atomic {
+        {% if database == "mysql" -%}
+        ui.warning("MySQL commits schema changes implicitly – those cannot be rolled back if a later migration fails.");
+        {% endif -%}
+        let mut transaction = connection
+            .begin()
+            .await
+            .context("Failed to start transaction!")?;
+        for (index, migration) in pending.iter().enumerate() {
+            if let Err(e) = transaction.apply(migration).await {
+                ui.warning(&format!(
+                    "Migration {} failed – rolling back all {} migration(s) of this run.",
+                    SqlxMigrator::migration_file(migration).display(),
+                    pending.len()
+                ));
+                transaction
+                    .rollback()
+                    .await
+                    .context("Failed to roll back transaction!")?;
+                return Err(anyhow!(e).context(format!(
+                    "Failed to apply migration {}! None of the {index} migration(s) applied before it were kept.",
+                    migration.version
+                )));
+            }
+            ui.log(&format!("Applied migration {}.", migration.version));
+        }
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction!")?;
+    } else {
+        for migration in &pending {
             connection
                 .apply(migration)
                 .await
-                .context("Failed to apply migration!")?;
+                .with_context(|| format!("Failed to apply migration {}!", migration.version))?;
             ui.log(&format!("Applied migration {}.", migration.version));
-            applied += 1;
+        }
+    }
+    close_connection(connection).await?;
+
+    Ok(pending.len() as i32)
+}
+
+/// Whether the local migration differs from the version that was applied to the database.
+fn has_drifted(migration: &Migration, applied: &AppliedMigration) -> bool {
+    migration.checksum != applied.checksum
+}
+
+/// Finds all applied migrations that were changed locally after they were applied.
+fn drifted_migrations<'a>(
+    migrator: &'a Migrator,
+    applied_migrations: &'a HashMap<i64, AppliedMigration>,
+) -> Vec<(&'a Migration, &'a AppliedMigration)> {
+    migrator
+        .iter()
+        .filter_map(|migration| {
+            applied_migrations
+                .get(&migration.version)
+                .filter(|applied| has_drifted(migration, applied))
+                .map(|applied| (migration, applied))
+        })
+        .collect()
+}
+
+/// Formats the start of a checksum as hex, which is enough to tell checksums apart.
+fn short_checksum(checksum: &[u8]) -> String {
+    checksum.iter().take(8).map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[derive(PartialEq)]
+enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied to the database but not present in the migrations directory
+    Missing,
+}
+
+struct MigrationStatus {
+    version: i64,
+    name: String,
+    /// The type of the local migration – `None` for migrations that are missing locally
+    migration_type: Option<MigrationType>,
+    state: MigrationState,
+    applied_on: Option<String>,
+    checksum_mismatch: bool,
+}
+
+async fn status(config: &DatabaseConfig, migrations_path: &Path) -> Result<Vec<MigrationStatus>, anyhow::Error> {
+    let migrator = SqlxMigrator::up_migrator(migrations_path)
+        .context("Failed to build migrator!")?;
+    let (mut connection, applied_migrations) = prepare_migrations(config, false).await?;
+
+    {% if database == "mysql" -%}
+    let query = "SELECT version, description, CAST(installed_on AS CHAR) FROM _sqlx_migrations ORDER BY version";
+    {%- else -%}
+    let query = "SELECT version, description, CAST(installed_on AS TEXT) FROM _sqlx_migrations ORDER BY version";
+    {%- endif %}
+    let mut applied_rows: HashMap<i64, (String, String)> = sqlx::query_as::<_, (i64, String, String)>(query)
+        .fetch_all(&mut connection)
+        .await
+        .context("Failed to load applied migrations!")?
+        .into_iter()
+        .map(|(version, description, applied_on)| (version, (description, applied_on)))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = migrator
+        .iter()
+        .map(|migration| {
+            let applied = applied_migrations.get(&migration.version);
+            MigrationStatus {
+                version: migration.version,
+                name: migration.description.to_string(),
+                migration_type: Some(migration.migration_type),
+                state: if applied.is_some() { MigrationState::Applied } else { MigrationState::Pending },
+                applied_on: applied_rows.remove(&migration.version).map(|(_, applied_on)| applied_on),
+                checksum_mismatch: applied.is_some_and(|applied| has_drifted(migration, applied)),
+            }
+        })
+        .collect();
+
+    statuses.extend(
+        applied_migrations
+            .keys()
+            .filter(|version| !migrator.iter().any(|migration| migration.version == **version))
+            .map(|version| {
+                let (name, applied_on) = applied_rows.remove(version).unzip();
+                MigrationStatus {
+                    version: *version,
+                    name: name.unwrap_or_default(),
+                    migration_type: None,
+                    state: MigrationState::Missing,
+                    applied_on,
+                    checksum_mismatch: false,
+                }
+            }),
+    );
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Formats the migration statuses as a table with one line per migration, preceded by a header.
+fn format_status_table(statuses: &[MigrationStatus]) -> Vec<String> {
+    let header = ["Version", "Name", "Type", "Status", "Applied at", "Checksum"].map(String::from);
+    let rows = statuses.iter().map(|status| {
+        vec![
+            status.version.to_string(),
+            status.name.clone(),
+            String::from(match status.migration_type {
+                Some(MigrationType::Simple) => "simple",
+                Some(_) => "reversible",
+                None => "-",
+            }),
+            String::from(match status.state {
+                MigrationState::Applied => "applied",
+                MigrationState::Pending => "pending",
+                MigrationState::Missing => "missing locally",
+            }),
+            status.applied_on.clone().unwrap_or_else(|| String::from("-")),
+            String::from(match (&status.state, status.checksum_mismatch) {
+                (_, true) => "mismatch",
+                (MigrationState::Applied, false) => "ok",
+                _ => "-",
+            }),
+        ]
+    });
+
+    format_table(header.to_vec(), rows)
+}
+
+/// Formats a table with left-aligned columns that are as wide as their widest cell.
+fn format_table(header: Vec<String>, rows: impl Iterator<Item = Vec<String>>) -> Vec<String> {
+    let table: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+
+    let mut widths = vec![0; table[0].len()];
+    for row in &table {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
         }
     }
 
-    Ok(applied)
+    table
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
 }
 
-async fn rollback(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path, steps: u32, to: Option<&str>) -> Result<i32, anyhow::Error> {
+/// Reverts the latest `steps` applied migrations (or all applied after the migration `to`) and returns the versions of the reverted ones.
+async fn rollback(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path, steps: u32, to: Option<&str>) -> Result<Vec<i64>, anyhow::Error> {
     let migrator = SqlxMigrator::down_migrator(migrations_path)
         .context("Failed to build migrator!")?;
-    let (mut connection, applied_migrations) = prepare_migrations(config).await?;
+    let (mut connection, applied_migrations) = prepare_migrations(config, false).await?;
 
     let mut applied_versions: Vec<i64> = applied_migrations.keys().copied().collect();
     applied_versions.sort_unstable();
     applied_versions.reverse();
 
     let target_version = if let Some(name) = to {
-        let version = migrator
-            .iter()
-            .find(|m| *m.description == *name)
-            .map(|m| m.version)
-            .with_context(|| format!("No migration found with name \"{name}\""))?;
+        let version = find_migration_version(&migrator, name)?;
 
         if !applied_migrations.contains_key(&version) {
             return Err(anyhow!("Migration \"{name}\" has not been applied."));
@@ -428,13 +909,13 @@ async fn rollback(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Pa
         None
     };
 
-    let mut reverted = 0;
+    let mut reverted = Vec::new();
     for version in applied_versions {
         if let Some(target) = target_version {
             if version <= target {
                 break;
             }
-        } else if reverted >= steps as i32 {
+        } else if reverted.len() >= steps as usize {
             break;
         }
 
@@ -444,27 +925,405 @@ async fn rollback(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Pa
                 .await
                 .with_context(|| format!("Failed to revert migration {version}!"))?;
             ui.log(&format!("Reverted migration {version}."));
-            reverted += 1;
+            reverted.push(version);
         }
     }
+    close_connection(connection).await?;
 
     Ok(reverted)
 }
 
-async fn seed(config: &DatabaseConfig) -> Result<(), anyhow::Error> {
-    let mut connection = get_db_client(config).await;
+/// Rolls back and reapplies the latest `steps` migrations and returns their number.
+///
+/// To verify that the down migrations restore the schema, the migrations are rolled back twice: the schema after the first rollback is compared with the schema after rolling back the reapplied migrations. This way, up migrations can be changed in between redos without the comparison failing.
+async fn redo(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path, steps: u32) -> Result<i32, anyhow::Error> {
+    let down_migrator = SqlxMigrator::down_migrator(migrations_path)
+        .context("Failed to build migrator!")?;
+    let (_, applied_migrations) = prepare_migrations(config, false).await?;
+    let mut applied_versions: Vec<i64> = applied_migrations.keys().copied().collect();
+    applied_versions.sort_unstable();
+    applied_versions.reverse();
+    if applied_versions.is_empty() {
+        return Err(anyhow!("No migrations have been applied yet!"));
+    }
+    // rolling back skips simple migrations so those would be left applied while older ones are reverted
+    let irreversible: Vec<String> = applied_versions
+        .iter()
+        .take(steps as usize)
+        .filter(|version| !down_migrator.iter().any(|migration| migration.version == **version))
+        .map(i64::to_string)
+        .collect();
+    if !irreversible.is_empty() {
+        return Err(anyhow!(
+            "Migration(s) {} cannot be redone as they have no down.sql!",
+            irreversible.join(", ")
+        ));
+    }
+
+    ui.log("Rolling back…");
+    ui.indent();
+    let result = rollback(ui, config, migrations_path, steps, None).await;
+    ui.outdent();
+    let reverted = result?;
+    let Some(highest_reverted) = reverted.iter().max() else {
+        return Ok(0);
+    };
+    let reapply = MigrateOptions {
+        to: Some(highest_reverted.to_string()),
+        ..MigrateOptions::default()
+    };
+    let schema_before = dump_schema(config).await?;
+
+    ui.log("Reapplying…");
+    ui.indent();
+    let result = migrate(ui, config, migrations_path, &reapply).await;
+    ui.outdent();
+    result?;
+
+    ui.log("Rolling back again to compare the schema…");
+    ui.indent();
+    let result = rollback(ui, config, migrations_path, steps, None).await;
+    ui.outdent();
+    result?;
+    let schema_after = dump_schema(config).await?;
+
+    ui.log("Reapplying…");
+    ui.indent();
+    let result = migrate(ui, config, migrations_path, &reapply).await;
+    ui.outdent();
+    result?;
 
-    let statements = fs::read_to_string("./db/seeds.sql")
-        .expect("Could not read seeds – make sure db/seeds.sql exists!");
+    if schema_before != schema_after {
+        ui.warning("The schema after rolling back differs from the schema before applying the migration(s):");
+        ui.indent();
+        for line in diff_lines(&schema_before, &schema_after) {
+            ui.log(&line);
+        }
+        ui.outdent();
+        return Err(anyhow!("The down migration(s) do not restore the schema!"));
+    }
+
+    Ok(reverted.len() as i32)
+}
+
+/// Lists the lines only present in `before` (prefixed with `-`) and the lines only present in `after` (prefixed with `+`).
+fn diff_lines(before: &str, after: &str) -> Vec<String> {
+    let before_lines: HashSet<&str> = before.lines().collect();
+    let after_lines: HashSet<&str> = after.lines().collect();
+
+    before
+        .lines()
+        .filter(|line| !after_lines.contains(line))
+        .map(|line| format!("- {line}"))
+        .chain(
+            after
+                .lines()
+                .filter(|line| !before_lines.contains(line))
+                .map(|line| format!("+ {line}")),
+        )
+        .collect()
+}
 
+/// Dumps the database's schema as SQL statements in a deterministic order, excluding sqlx's migrations table.
+{%- if database == "sqlite" %}
+async fn dump_schema(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let mut connection = get_db_client(config).await?;
+
+    let statements: Vec<(String,)> = sqlx::query_as(
+        "SELECT sql FROM sqlite_master \
+        WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%' AND name <> '_sqlx_migrations' \
+        ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'index' THEN 1 WHEN 'view' THEN 2 ELSE 3 END, name",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump schema!")?;
+
+    Ok(statements
+        .into_iter()
+        .map(|(statement,)| format!("{statement};\n"))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+{%- elsif database == "mysql" %}
+async fn dump_schema(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let mut connection = get_db_client(config).await?;
+
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT CAST(table_name AS CHAR) FROM information_schema.tables \
+        WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE' AND table_name <> '_sqlx_migrations' \
+        ORDER BY table_name",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump schema!")?;
+
+    // the auto increment counter depends on the data in the table, not its schema
+    let auto_increment = Regex::new(r" AUTO_INCREMENT=\d+").expect("Invalid regex!");
+    // tables are created in alphabetical order so foreign keys can only be checked once all of them exist
+    let mut statements = vec![String::from("SET FOREIGN_KEY_CHECKS = 0;\n")];
+    for (table,) in tables {
+        let (_, create_table): (String, String) = sqlx::query_as(&format!("SHOW CREATE TABLE `{table}`"))
+            .fetch_one(&mut connection)
+            .await
+            .with_context(|| format!("Failed to dump table {table}!"))?;
+        statements.push(format!("{};\n", auto_increment.replace(&create_table, "")));
+    }
+    statements.push(String::from("SET FOREIGN_KEY_CHECKS = 1;\n"));
+
+    Ok(statements.join("\n"))
+}
+{%- else %}
+async fn dump_schema(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let mut connection = get_db_client(config).await?;
+    let mut statements = Vec::new();
+
+    let extensions: Vec<(String,)> = sqlx::query_as(
+        "SELECT quote_ident(extname) FROM pg_extension WHERE extname <> 'plpgsql' ORDER BY extname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump extensions!")?;
+    for (extension,) in extensions {
+        statements.push(format!("CREATE EXTENSION IF NOT EXISTS {extension};\n"));
+    }
+
+    let enums: Vec<(String, String)> = sqlx::query_as(
+        "SELECT quote_ident(t.typname), string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder) \
+        FROM pg_type t \
+        JOIN pg_enum e ON e.enumtypid = t.oid \
+        JOIN pg_namespace n ON n.oid = t.typnamespace \
+        WHERE n.nspname = 'public' \
+        GROUP BY t.typname ORDER BY t.typname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump types!")?;
+    for (name, labels) in enums {
+        statements.push(format!("CREATE TYPE {name} AS ENUM ({labels});\n"));
+    }
+
+    // sequences backing identity columns are created along with the columns
+    let sequences: Vec<(String,)> = sqlx::query_as(
+        "SELECT quote_ident(c.relname) FROM pg_class c \
+        JOIN pg_namespace n ON n.oid = c.relnamespace \
+        WHERE c.relkind = 'S' AND n.nspname = 'public' \
+        AND NOT EXISTS (SELECT 1 FROM pg_depend d WHERE d.objid = c.oid AND d.deptype = 'i') \
+        ORDER BY c.relname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump sequences!")?;
+    for (sequence,) in sequences {
+        statements.push(format!("CREATE SEQUENCE {sequence};\n"));
+    }
+
+    let tables: Vec<(String, String)> = sqlx::query_as(
+        "SELECT c.relname::text, quote_ident(c.relname) FROM pg_class c \
+        JOIN pg_namespace n ON n.oid = c.relnamespace \
+        WHERE c.relkind IN ('r', 'p') AND n.nspname = 'public' AND c.relname <> '_sqlx_migrations' \
+        ORDER BY c.relname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump tables!")?;
+    for (table, quoted_table) in tables {
+        let columns: Vec<(String, String, bool, Option<String>, String, String)> = sqlx::query_as(
+            "SELECT quote_ident(a.attname), format_type(a.atttypid, a.atttypmod), a.attnotnull, \
+            pg_get_expr(d.adbin, d.adrelid), a.attidentity::text, a.attgenerated::text \
+            FROM pg_attribute a \
+            LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+            WHERE a.attrelid = (SELECT c.oid FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace WHERE n.nspname = 'public' AND c.relname = $1) \
+            AND a.attnum > 0 AND NOT a.attisdropped \
+            ORDER BY a.attnum",
+        )
+        .bind(&table)
+        .fetch_all(&mut connection)
+        .await
+        .with_context(|| format!("Failed to dump table {table}!"))?;
+
+        let columns: Vec<String> = columns
+            .into_iter()
+            .map(|(name, data_type, not_null, default, identity, generated)| {
+                let mut column = format!("    {name} {data_type}");
+                match (identity.as_str(), generated.as_str(), default) {
+                    ("a", _, _) => column.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                    ("d", _, _) => column.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                    (_, "s", Some(expression)) => column.push_str(&format!(" GENERATED ALWAYS AS ({expression}) STORED")),
+                    (_, _, Some(default)) => column.push_str(&format!(" DEFAULT {default}")),
+                    _ => {}
+                }
+                if not_null {
+                    column.push_str(" NOT NULL");
+                }
+                column
+            })
+            .collect();
+        statements.push(format!("CREATE TABLE {quoted_table} (\n{}\n);\n", columns.join(",\n")));
+    }
+
+    // constraints are added once all tables exist so that foreign keys can reference any table
+    let constraints: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT quote_ident(cl.relname), quote_ident(co.conname), pg_get_constraintdef(co.oid) \
+        FROM pg_constraint co \
+        JOIN pg_class cl ON cl.oid = co.conrelid \
+        JOIN pg_namespace n ON n.oid = cl.relnamespace \
+        WHERE n.nspname = 'public' AND cl.relname <> '_sqlx_migrations' AND co.contype IN ('p', 'u', 'c', 'x', 'f') \
+        ORDER BY co.contype = 'f', cl.relname, co.conname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump constraints!")?;
+    for (table, name, definition) in constraints {
+        statements.push(format!("ALTER TABLE ONLY {table} ADD CONSTRAINT {name} {definition};\n"));
+    }
+
+    // indexes backing constraints are created along with the constraints
+    let indexes: Vec<(String,)> = sqlx::query_as(
+        "SELECT pg_get_indexdef(i.indexrelid) FROM pg_index i \
+        JOIN pg_class t ON t.oid = i.indrelid \
+        JOIN pg_class ic ON ic.oid = i.indexrelid \
+        JOIN pg_namespace n ON n.oid = t.relnamespace \
+        WHERE n.nspname = 'public' AND t.relname <> '_sqlx_migrations' \
+        AND NOT EXISTS (SELECT 1 FROM pg_constraint co WHERE co.conindid = i.indexrelid AND co.conrelid = i.indrelid AND co.contype IN ('p', 'u', 'x')) \
+        ORDER BY t.relname, ic.relname",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .context("Failed to dump indexes!")?;
+    for (index,) in indexes {
+        statements.push(format!("{index};\n"));
+    }
+
+    Ok(statements.join("\n"))
+}
+{%- endif %}
+
+/// Dumps the database's schema to [`SCHEMA_FILE`], followed by the applied migrations so that loading the schema marks them as applied.
+async fn write_schema_file(config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let schema = dump_schema(config).await?;
+
+    let (mut connection, _) = prepare_migrations(config, false).await?;
+    let migrations: Vec<(i64, String, Vec<u8>)> =
+        sqlx::query_as("SELECT version, description, checksum FROM _sqlx_migrations WHERE success ORDER BY version")
+            .fetch_all(&mut connection)
+            .await
+            .context("Failed to load applied migrations!")?;
+
+    let mut contents = String::from("-- This file is generated by `cargo db schema dump` – do not edit it manually.\n-- Load it into a fresh database with `cargo db schema load`.\n\n");
+    contents.push_str(&schema);
+    if !migrations.is_empty() {
+        let values: Vec<String> = migrations
+            .iter()
+            .map(|(version, description, checksum)| {
+                let checksum: String = checksum.iter().map(|byte| format!("{byte:02x}")).collect();
+                {% if database == "postgres" -%}
+                format!("({version}, '{}', TRUE, '\\x{checksum}', 0)", description.replace('\'', "''"))
+                {%- else -%}
+                format!("({version}, '{}', TRUE, X'{checksum}', 0)", description.replace('\'', "''"))
+                {%- endif %}
+            })
+            .collect();
+        contents.push_str(&format!(
+            "\nINSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES\n{};\n",
+            values.join(",\n")
+        ));
+    }
+
+    let path = db_package_root()?.join("schema.sql");
+    fs::write(&path, contents).with_context(|| format!("Could not write file \"{}\"!", path.display()))?;
+
+    Ok(())
+}
+
+/// Updates [`SCHEMA_FILE`] after the schema of the development database changed.
+async fn update_schema_file(ui: &mut UI<'_>, env: &Environment, config: &DatabaseConfig) {
+    if *env != Environment::Development {
+        return;
+    }
+
+    match write_schema_file(config).await {
+        Ok(()) => ui.log(&format!("Updated {SCHEMA_FILE}.")),
+        Err(e) => ui.warning(&format!("Could not update {SCHEMA_FILE}: {e}")),
+    }
+}
+
+/// Recreates the database from [`SCHEMA_FILE`] instead of running all migrations.
+async fn load_schema(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let path = db_package_root()?.join("schema.sql");
+    let schema = fs::read_to_string(&path).with_context(|| {
+        format!("Could not read file \"{}\" – run `cargo db schema dump` to create it!", path.display())
+    })?;
+
+    ui.log("Dropping database…");
+    drop(config).await?;
+    ui.log("Recreating database…");
+    let db_name = create(config).await?;
+    ui.log("Loading schema…");
+    let (mut connection, _) = prepare_migrations(config, false).await?;
+    sqlx::raw_sql(&schema)
+        .execute(&mut connection)
+        .await
+        .context("Failed to load schema!")?;
+    close_connection(connection).await?;
+
+    Ok(db_name)
+}
+
+/// Finds the version of the migration with the given name or version.
+fn find_migration_version(migrator: &Migrator, name_or_version: &str) -> Result<i64, anyhow::Error> {
+    migrator
+        .iter()
+        .find(|m| *m.description == *name_or_version || m.version.to_string() == name_or_version)
+        .map(|m| m.version)
+        .with_context(|| format!("No migration found with name or version \"{name_or_version}\""))
+}
+
+/// Runs the SQL seeds for the environment – `db/seeds.sql` for all environments if it exists, followed by the files in `db/seeds/<env>` in lexical order.
+async fn seed(ui: &mut UI<'_>, env: &Environment, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let db_root = db_package_root()?;
+    let mut seed_files = Vec::new();
+
+    let shared_seeds = db_root.join("seeds.sql");
+    if shared_seeds.is_file() {
+        seed_files.push(shared_seeds);
+    }
+
+    let env_seeds_dir = db_root.join("seeds").join(env.to_string());
+    if env_seeds_dir.is_dir() {
+        let mut env_seeds: Vec<PathBuf> = fs::read_dir(&env_seeds_dir)
+            .with_context(|| format!("Could not read directory \"{}\"!", env_seeds_dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("sql"))
+            .collect();
+        env_seeds.sort();
+        seed_files.extend(env_seeds);
+    }
+
+    if seed_files.is_empty() {
+        ui.log(&format!("No SQL seeds found in db/seeds/{env}."));
+        return Ok(());
+    }
+
+    let mut connection = get_db_client(config).await?;
     let mut transaction = connection
         .begin()
         .await
         .context("Failed to start transaction!")?;
-    transaction
-        .execute(statements.as_str())
-        .await
-        .context("Failed to execute seeds!")?;
+    for seed_file in &seed_files {
+        let name = Path::new("db").join(seed_file.strip_prefix(&db_root).unwrap_or(seed_file));
+        let statements = fs::read_to_string(seed_file)
+            .with_context(|| format!("Could not read file \"{}\"!", name.display()))?;
+        if statements.trim().is_empty() {
+            continue;
+        }
+
+        transaction
+            .execute(statements.as_str())
+            .await
+            .with_context(|| format!("Failed to execute seeds in {}!", name.display()))?;
+        ui.log(&format!("Ran {}.", name.display()));
+    }
     transaction
         .commit()
         .await
@@ -473,6 +1332,34 @@ async fn seed(config: &DatabaseConfig) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Runs the `seed` binary which generates fake records via the db crate's `seeds` module.
+///
+/// The binary is built separately as building it requires the db crate, whose queries can only be compiled against a migrated database.
+async fn generate_seeds(env: &Environment, count: usize, no_color: bool, quiet: bool) -> Result<(), anyhow::Error> {
+    let cargo = get_cargo_path()?;
+    let mut command = tokio::process::Command::new(&cargo);
+    command
+        .args(["run", "--package", env!("CARGO_PKG_NAME"), "--features", "seeds", "--bin", "seed", "--"])
+        .args(["--env", &env.to_string(), "--count", &count.to_string()])
+        .current_dir(db_package_root()?.join(".."));
+    if no_color {
+        command.arg("--no-color");
+    }
+    if quiet {
+        command.arg("--quiet");
+    }
+
+    let status = command
+        .status()
+        .await
+        .with_context(|| format!("Could not run {cargo} run --bin seed!"))?;
+    if !status.success() {
+        return Err(anyhow!("Generating seeds failed!"));
+    }
+
+    Ok(())
+}
+
 async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path) -> Result<String, anyhow::Error> {
     ui.log("Dropping database…");
     drop(config).await?;
@@ -480,7 +1367,7 @@ async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path)
     let db_name = create(config).await?;
     ui.log("Migrating database…");
     ui.indent();
-    let migration_result = migrate(ui, config, migrations_path).await;
+    let migration_result = migrate(ui, config, migrations_path, &MigrateOptions::default()).await;
     ui.outdent();
 
     match migration_result {
@@ -489,35 +1376,368 @@ async fn reset(ui: &mut UI<'_>, config: &DatabaseConfig, migrations_path: &Path)
     }
 }
 
-fn get_db_config(config: &DatabaseConfig) -> PgConnectOptions {
-    let db_url = Url::parse(&config.url).expect("Invalid DATABASE_URL!");
-    ConnectOptions::from_url(&db_url).expect("Invalid DATABASE_URL!")
+/// Starts the database's interactive client, falling back to a minimal built-in console if it is not installed.
+async fn console(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let mut command = tokio::process::Command::new(CONSOLE_CLIENT);
+    {%- if database == "sqlite" %}
+    command.arg(get_db_name(config)?);
+    {%- elsif database == "mysql" %}
+    let db_url = Url::parse(&config.url).context("Invalid database URL!")?;
+    command
+        .arg("--protocol=TCP")
+        .arg("--host")
+        .arg(db_url.host_str().unwrap_or("localhost"))
+        .arg("--port")
+        .arg(db_url.port().unwrap_or(DEFAULT_PORT).to_string())
+        .arg("--user")
+        .arg(db_url.username())
+        .arg(db_url.path().trim_start_matches('/'));
+    if let Some(password) = db_url.password() {
+        // passing the password via the environment keeps it out of the process list
+        command.env("MYSQL_PWD", password);
+    }
+    {%- else %}
+    let db_url = Url::parse(&config.url).context("Invalid database URL!")?;
+    command
+        .arg("--host")
+        .arg(db_url.host_str().unwrap_or("localhost"))
+        .arg("--port")
+        .arg(db_url.port().unwrap_or(DEFAULT_PORT).to_string())
+        .arg("--username")
+        .arg(db_url.username())
+        .arg("--dbname")
+        .arg(db_url.path().trim_start_matches('/'));
+    if let Some(password) = db_url.password() {
+        // passing the password via the environment keeps it out of the process list
+        command.env("PGPASSWORD", password);
+    }
+    {%- endif %}
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            ui.warning(&format!("{CONSOLE_CLIENT} is not installed – starting a minimal built-in console instead."));
+            return builtin_console(ui, config).await;
+        }
+        Err(e) => return Err(anyhow!(e).context(format!("Could not run {CONSOLE_CLIENT}!"))),
+    };
+
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status.with_context(|| format!("Could not run {CONSOLE_CLIENT}!"))?,
+            // the client handles Ctrl+C itself, e.g. to cancel the current query, so it must not terminate this process
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    };
+    if !status.success() {
+        return Err(anyhow!("{CONSOLE_CLIENT} exited with {status}!"));
+    }
+
+    Ok(())
+}
+
+/// A minimal console that reads SQL statements terminated by a semicolon from stdin and prints their results.
+async fn builtin_console(ui: &mut UI<'_>, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    let db_name = get_db_name(config)?;
+    let mut connection = get_db_client(config).await?;
+    ui.info(r"Enter SQL statements terminated by a semicolon, or \q to quit.");
+
+    let mut reader = tokio::io::BufReader::new(stdin());
+    let mut sql = String::new();
+    loop {
+        print!("{}", if sql.is_empty() { format!("{db_name}> ") } else { String::from("... ") });
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+        if sql.is_empty() && matches!(line.trim(), r"\q" | "exit" | "quit") {
+            break;
+        }
+        sql.push_str(&line);
+        if !sql.trim_end().ends_with(';') {
+            continue;
+        }
+
+        match execute_sql(&mut connection, &sql).await {
+            Ok(lines) => {
+                for line in lines {
+                    ui.log(&line);
+                }
+            }
+            Err(e) => ui.error(&e.to_string(), &e),
+        }
+        sql.clear();
+    }
+
+    Ok(())
+}
+
+/// Executes the SQL and formats the returned rows as a table.
+async fn execute_sql(connection: &mut {{type_prefix}}Connection, sql: &str) -> Result<Vec<String>, anyhow::Error> {
+    let rows = sqlx::raw_sql(sql)
+        .fetch_all(&mut *connection)
+        .await
+        .context("Failed to execute SQL!")?;
+
+    let Some(first_row) = rows.first() else {
+        return Ok(vec![String::from("(0 rows)")]);
+    };
+    let header = first_row
+        .columns()
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+    let values = rows
+        .iter()
+        .map(|row| (0..row.len()).map(|index| format_value(row, index)).collect());
+
+    let mut lines = format_table(header, values);
+    lines.push(format!("({} row(s))", rows.len()));
+    Ok(lines)
+}
+
+/// Formats a value of any of the common column types for printing, falling back to the name of the type for others.
+fn format_value(row: &{{type_prefix}}Row, index: usize) -> String {
+    format_value_as::<String>(row, index)
+        .or_else(|| format_value_as::<i64>(row, index))
+        .or_else(|| format_value_as::<i32>(row, index))
+        .or_else(|| format_value_as::<i16>(row, index))
+        {%- if database == "mysql" %}
+        .or_else(|| format_value_as::<u64>(row, index))
+        {%- endif %}
+        .or_else(|| format_value_as::<f64>(row, index))
+        .or_else(|| format_value_as::<f32>(row, index))
+        .or_else(|| format_value_as::<bool>(row, index))
+        .or_else(|| format_value_as::<DateTime<Utc>>(row, index))
+        .or_else(|| format_value_as::<NaiveDateTime>(row, index))
+        .or_else(|| format_value_as::<NaiveDate>(row, index))
+        .or_else(|| format_value_as::<NaiveTime>(row, index))
+        .or_else(|| format_value_as::<Uuid>(row, index))
+        .or_else(|| {
+            let bytes: Option<Vec<u8>> = row.try_get(index).ok()?;
+            Some(bytes.map_or_else(
+                || String::from("NULL"),
+                |bytes| format!("0x{}", bytes.iter().map(|byte| format!("{byte:02x}")).collect::<String>()),
+            ))
+        })
+        .unwrap_or_else(|| format!("<{}>", row.column(index).type_info().name()))
+}
+
+/// Formats the value if it can be decoded as `T`.
+fn format_value_as<'r, T>(row: &'r {{type_prefix}}Row, index: usize) -> Option<String>
+where
+    T: sqlx::Decode<'r, sqlx::{{database_type}}> + sqlx::Type<sqlx::{{database_type}}> + ToString,
+{
+    let value: Option<T> = row.try_get(index).ok()?;
+    Some(value.map_or_else(|| String::from("NULL"), |value| value.to_string()))
+}
+
+fn get_db_config(config: &DatabaseConfig) -> Result<{{type_prefix}}ConnectOptions, anyhow::Error> {
+    let db_url = Url::parse(&config.url).context("Invalid database URL!")?;
+    ConnectOptions::from_url(&db_url).context("Invalid database URL!")
 }
 
-async fn get_db_client(config: &DatabaseConfig) -> PgConnection {
-    let db_config = get_db_config(config);
-    let connection: PgConnection = Connection::connect_with(&db_config)
+async fn get_db_client(config: &DatabaseConfig) -> Result<{{type_prefix}}Connection, anyhow::Error> {
+    let db_config = get_db_config(config)?;
+    Connection::connect_with(&db_config)
         .await
-        .expect("Should be able to connect to the database!");
+        .with_context(|| format!("Could not connect to database {}!", describe_database(config)))
+}
 
+/// Closes a connection that changed the database, waiting for the changes to be written.
+{%- if database == "sqlite" %}
+///
+/// The changes are moved from SQLite's write-ahead log into the database file first so that copies of the file include them.
+{%- endif %}
+async fn close_connection({% if database == "sqlite" %}mut {% endif %}connection: {{type_prefix}}Connection) -> Result<(), anyhow::Error> {
+    {%- if database == "sqlite" %}
+    connection
+        .execute("PRAGMA wal_checkpoint(TRUNCATE)")
+        .await
+        .context("Failed to checkpoint the database!")?;
+    {%- endif %}
     connection
+        .close()
+        .await
+        .context("Failed to close the database connection!")
 }
 
-async fn get_root_db_client(config: &DatabaseConfig) -> PgConnection {
-    let db_config = get_db_config(config);
-    let root_db_config = db_config.clone().database("postgres");
-    let connection: PgConnection = Connection::connect_with(&root_db_config)
+{% if database == "postgres" -%}
+async fn get_root_db_client(config: &DatabaseConfig) -> Result<PgConnection, anyhow::Error> {
+    let root_db_config = get_db_config(config)?.database("postgres");
+    Connection::connect_with(&root_db_config)
         .await
-        .expect("Should be able to connect to the database!");
+        .with_context(|| format!("Could not connect to the database server for {}!", describe_database(config)))
+}
 
-    connection
+fn get_db_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_config = get_db_config(config)?;
+    let db_name = db_config
+        .get_database()
+        .context("Failed to get database name!")?;
+
+    Ok(String::from(db_name))
+}
+{%- elsif database == "sqlite" -%}
+fn get_db_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_config = get_db_config(config)?;
+    Ok(db_config.get_filename().display().to_string())
+}
+{%- else -%}
+async fn get_root_db_client(config: &DatabaseConfig) -> Result<MySqlConnection, anyhow::Error> {
+    // connect to the server without selecting a database as the database might not exist (yet)
+    let mut db_url = Url::parse(&config.url).context("Invalid database URL!")?;
+    db_url.set_path("");
+    let root_db_config = MySqlConnectOptions::from_url(&db_url).context("Invalid database URL!")?;
+    Connection::connect_with(&root_db_config)
+        .await
+        .with_context(|| format!("Could not connect to the database server for {}!", describe_database(config)))
+}
+
+fn get_db_name(config: &DatabaseConfig) -> Result<String, anyhow::Error> {
+    let db_url = Url::parse(&config.url).context("Invalid database URL!")?;
+    let db_name = db_url.path().trim_start_matches('/');
+    if db_name.is_empty() {
+        return Err(anyhow!("Failed to get database name!"));
+    }
+
+    Ok(String::from(db_name))
+}
+{%- endif %}
+
+/// Describes the database the URL points to for error messages – without the credentials the URL might contain.
+fn describe_database(config: &DatabaseConfig) -> String {
+    {%- if database == "sqlite" %}
+    get_db_name(config).unwrap_or_else(|_| String::from("(invalid URL)"))
+    {%- else %}
+    let Ok(db_url) = Url::parse(&config.url) else {
+        return String::from("(invalid URL)");
+    };
+    format!(
+        "{} on {}:{}",
+        db_url.path().trim_start_matches('/'),
+        db_url.host_str().unwrap_or("localhost"),
+        db_url.port().unwrap_or(DEFAULT_PORT)
+    )
+    {%- endif %}
+}
+
+/// Describes where the database URL for the environment is read from – see `load_config`.
+fn database_url_source(env: &Environment) -> String {
+    let dotenv_file = match env {
+        Environment::Development => ".env",
+        Environment::Test => ".env.test",
+        Environment::Production => return String::from("the process environment"),
+    };
+    match std::env::var("APP_DOTENV_CONFIG_DIR") {
+        Ok(dir) => Path::new(&dir).join(dotenv_file).display().to_string(),
+        Err(_) => String::from(dotenv_file),
+    }
+}
+{% unless database == "sqlite" %}
+/// Connects to the database server, retrying for up to `wait`, e.g. while the server is still starting up in a container.
+async fn wait_for_database_server(ui: &mut UI<'_>, config: &DatabaseConfig, wait: Option<Duration>) -> Result<(), anyhow::Error> {
+    let deadline = Instant::now() + wait.unwrap_or_default();
+    let mut waiting = false;
+    loop {
+        match get_root_db_client(config).await {
+            Ok(connection) => {
+                connection.close().await.ok();
+                return Ok(());
+            }
+            Err(_) if Instant::now() < deadline => {
+                if !waiting {
+                    ui.info(&format!("Waiting for database {}…", describe_database(config)));
+                    waiting = true;
+                }
+                tokio::time::sleep(WAIT_RETRY_INTERVAL).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 }
 
+/// Parses a duration like `30s`, `2m`, or `30` (seconds) for the `--wait` option.
+fn parse_wait(value: &str) -> Result<Duration, anyhow::Error> {
+    let (amount, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, "s"), |index| value.split_at(index));
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| anyhow!(r#"Invalid duration: "{value}"!"#))?;
+    match unit {
+        "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount * 60)),
+        _ => Err(anyhow!(r#"Invalid duration: "{value}"! Use e.g. 30s or 2m."#)),
+    }
+}
+{% endunless %}
 fn get_cargo_path() -> Result<String, anyhow::Error> {
     std::env::var("CARGO")
         .map_err(|_| anyhow!("Please invoke me using Cargo, e.g.: `cargo db <ARGS>`"))
 }
 
+/// Ensure that the database URL is valid{% unless database == "sqlite" %} and the database server accepts connections{% endunless %}, pointing to where the URL was read from otherwise.
+async fn ensure_database_reachable({% if database == "sqlite" %}_{% endif %}ui: &mut UI<'_>, cli: &Cli, config: &DatabaseConfig) -> Result<(), anyhow::Error> {
+    get_db_config(config).with_context(|| {
+        format!(
+            "Invalid database URL for the {} environment! Check APP_DATABASE__URL in {}.",
+            cli.env,
+            database_url_source(&cli.env)
+        )
+    })?;
+    {%- unless database == "sqlite" %}
+    wait_for_database_server(ui, config, cli.wait).await.with_context(|| {
+        format!(
+            "Could not connect to the {} database {}! Check APP_DATABASE__URL in {} and that the database server is running.",
+            cli.env,
+            describe_database(config),
+            database_url_source(&cli.env)
+        )
+    })?;
+    {%- endunless %}
+
+    Ok(())
+}
+
+/// Ensure that a destructive command may run against the database.
+///
+/// The production database as well as databases marked as `protected` in the configuration can only be changed
+/// destructively when `--force` is passed and the user confirms by typing the database's name.
+async fn ensure_unprotected_or_confirmed(
+    ui: &mut UI<'_>,
+    env: &Environment,
+    config: &DatabaseConfig,
+    force: bool,
+) -> Result<(), anyhow::Error> {
+    if *env != Environment::Production && !config.protected {
+        return Ok(());
+    }
+
+    if !force {
+        return Err(anyhow!(
+            "The {env} database is protected! Pass --force to run this command against it anyway."
+        ));
+    }
+
+    let db_name = get_db_name(config)?;
+    ui.warning(&format!(
+        "This command irreversibly changes or deletes data in the protected {env} database {db_name}. \
+        Type the name of the database to continue:"
+    ));
+
+    let mut buf = String::new();
+    let mut reader = tokio::io::BufReader::new(stdin());
+    reader.read_line(&mut buf).await?;
+    if buf.trim() != db_name {
+        return Err(anyhow!("The database name did not match – aborting."));
+    }
+
+    Ok(())
+}
+
 /// Ensure that the correct version of sqlx-cli is installed,
 /// and install it if it isn't.
 async fn ensure_sqlx_cli_installed(ui: &mut UI<'_>) -> Result<(), anyhow::Error> {
@@ -1,15 +1,16 @@
 use anyhow::{anyhow, Context};
 use clap::{Parser, Subcommand};
 use cruet::{
-    case::{snake::to_snake_case, {%- if template_type != "minimal" -%}to_class_case{%- endif -%}},
-{% if template_type != "minimal" -%}
+    case::{snake::to_snake_case, {%- if with_db -%}to_class_case, to_pascal_case{%- endif -%}},
+{% if with_db -%}
     string::{pluralize::to_plural, singularize::to_singular},
 {% endif -%}
 };
 use guppy::{graph::PackageGraph, MetadataCommand};
 use liquid::Template;
+use proc_macro2::LineColumn;
 use {{crate_name}}_cli::util::ui::UI;
-{% if template_type != "minimal" -%}
+{% if with_db -%}
 use regex::Regex;
 use std::collections::HashMap;
 {% endif -%}
@@ -17,7 +18,8 @@ use std::fs::{self, File, OpenOptions};
 use std::io::prelude::*;
 use std::path::Path;
 use std::process::ExitCode;
-{% if template_type != "minimal" -%}
+use syn::{spanned::Spanned, visit::Visit, Expr, ExprMethodCall, Item, Lit, UseTree};
+{% if with_db -%}
 use std::time::SystemTime;
 use std::path::PathBuf;
 {% endif -%}
@@ -25,6 +27,11 @@ use std::path::PathBuf;
 static BLUEPRINTS_DIR: include_dir::Dir =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/blueprints");
 
+{% if with_db -%}
+/// The database backend the project uses – one of "postgres", "mysql", or "sqlite".
+const DATABASE: &str = "{{database}}";
+
+{% endif -%}
 #[tokio::main]
 async fn main() -> ExitCode {
     let mut stdout = std::io::stdout();
@@ -76,7 +83,7 @@ enum Commands {
         #[arg(help = "The name of the controller.")]
         name: String,
     },
-    {% if template_type != "minimal" -%}
+    {% if with_db -%}
     #[command(about = "Generate a migration")]
     Migration {
         #[arg(help = "The name of the migration.")]
@@ -85,12 +92,12 @@ enum Commands {
         #[arg(long, help = "Generate a simple (non-reversible) migration as a single .sql file.")]
         simple: bool,
     },
-    #[command(about = "Generate an entity. The created entity will have a `id` field of type `Uuid`.")]
+    #[command(about = "Generate an entity and a migration creating its table. The created entity will have a `id` field of type `Uuid`.")]
     Entity {
         #[arg(help = "The name of the entity.")]
         name: String,
         #[arg(
-            help = "The fields of the entity, each given as '<name>:<Rust type>'. Supported types are bool, i8, i16, i32, i64, f32, f64, String"
+            help = "The fields of the entity, each given as '<name>:<Rust type>[:<modifier>…]', e.g. 'title:String:max=100:unique' or 'published_at:Option<DateTime<Utc>>'. Supported types are bool, i8, i16, i32, i64, f32, f64, String, Uuid, DateTime<Utc>, {% unless database == "sqlite" %}Decimal, {% endunless %}Vec<u8>, serde_json::Value, enum=<variant>,<variant>,…, and references, e.g. 'post:references' for a post_id field referencing the posts table, each optionally wrapped in Option<…>. Supported modifiers are unique, index, default=<value>, min=<value>, max=<value>, and on_delete=cascade|restrict|set_null for references."
         )]
         fields: Vec<String>,
     },
@@ -103,11 +110,19 @@ enum Commands {
     CrudController {
         #[arg(help = "The name of the entity the controller is for.")]
         name: String,
+        #[arg(long, help = "The entity the controller's entity references, e.g. post for comments, to generate an action for the nested route /posts/{id}/comments.")]
+        parent: Option<String>,
+        #[arg(long, help = "Place the routes of the actions that write data behind the auth middleware's route_layer.")]
+        auth: bool,
     },
     #[command(about = "Generate a test for a CRUD controller")]
     CrudControllerTest {
         #[arg(help = "The name of the entity the controller is for.")]
         name: String,
+        #[arg(long, help = "The entity the controller's entity references, e.g. post for comments, to generate a test for the nested route /posts/{id}/comments.")]
+        parent: Option<String>,
+        #[arg(long, help = "Authenticate the requests to the actions that write data as those are routed behind the auth middleware.")]
+        auth: bool,
     },
     {% endif -%}
 }
@@ -127,7 +142,7 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
             let file_name = generate_controller(&name, cli.r#override)
                 .context("Could not generate controller!")?;
             ui.success(&format!("Generated controller {}.", &file_name));
-            ui.info("Do not forget to route the controller's actions in ./web/src/routes.rs!");
+            route_actions(ui, &name, controller_routes(&name));
             ui.info("Generating test for controller…");
             let file_name = generate_controller_test(&name, cli.r#override)
                 .context("Could not generate test for controller!")?;
@@ -141,7 +156,7 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
             ui.success(&format!("Generated test for controller {}.", &file_name));
             Ok(())
         }
-        {% if template_type != "minimal" -%}
+        {% if with_db -%}
         Commands::Migration { name, simple } => {
             ui.info("Generating migration…");
             if simple {
@@ -149,7 +164,7 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
                     .context("Could not generate migration!")?;
                 ui.success(&format!("Generated empty migration {}.", file_name.display()));
             } else {
-                let (up_file_name, down_file_name) = generate_migration(&name, cli.r#override)
+                let (up_file_name, down_file_name) = generate_migration(&name, "", "", cli.r#override)
                     .context("Could not generate migration!")?;
                 ui.success(&format!("Generated empty migration {}.", up_file_name.display()));
                 ui.success(&format!("Generated empty migration {}.", down_file_name.display()));
@@ -162,6 +177,12 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
             let struct_name = generate_entity(&name, &fields, cli.r#override)
                 .context("Could not generate entity!")?;
             ui.success(&format!("Generated entity {}.", &struct_name));
+            ui.info("Generating migration for entity…");
+            let (up_file_name, down_file_name) = generate_entity_migration(&name, &fields, cli.r#override)
+                .context("Could not generate migration for entity!")?;
+            ui.success(&format!("Generated migration {}.", up_file_name.display()));
+            ui.success(&format!("Generated migration {}.", down_file_name.display()));
+            ui.info("Run `cargo db migrate` to create the table.");
             Ok(())
         }
         Commands::EntityTestHelper { name } => {
@@ -174,13 +195,20 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
             ));
             Ok(())
         }
-        Commands::CrudController { name } => {
+        Commands::CrudController { name, parent, auth } => {
+            if auth {
+                ensure_auth()?;
+            }
             ui.info("Generating CRUD controller…");
-            let file_name = generate_crud_controller(&name, cli.r#override)
+            let file_name = generate_crud_controller(&name, parent.as_deref(), cli.r#override)
                 .context("Could not generate CRUD controller!")?;
             ui.success(&format!("Generated CRUD controller {}.", &file_name));
-            ui.info("Do not forget to route the controller's actions in ./web/src/routes.rs!");
-            let file_name = generate_crud_controller_test(&name, cli.r#override)
+            let has_entity = has_entity(&name);
+            if !has_entity {
+                ui.info("The controller's actions are stubs as the entity does not exist yet – generate the entity with `cargo generate entity` first to get a working controller.");
+            }
+            route_actions(ui, &name, crud_routes(&name, parent.as_deref(), has_entity, auth));
+            let file_name = generate_crud_controller_test(&name, parent.as_deref(), auth, cli.r#override)
                 .context("Could not generate test for CRUD controller!")?;
             ui.success(&format!(
                 "Generated test for CRUD controller {}.",
@@ -188,9 +216,12 @@ fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
             ));
             Ok(())
         }
-        Commands::CrudControllerTest { name } => {
+        Commands::CrudControllerTest { name, parent, auth } => {
+            if auth {
+                ensure_auth()?;
+            }
             ui.info("Generating test for CRUD controller…");
-            let file_name = generate_crud_controller_test(&name, cli.r#override)
+            let file_name = generate_crud_controller_test(&name, parent.as_deref(), auth, cli.r#override)
                 .context("Could not generate test for CRUD controller!")?;
             ui.success(&format!(
                 "Generated test for CRUD controller {}.",
@@ -272,7 +303,7 @@ fn generate_controller_test(name: &str, r#override: bool) -> Result<String, anyh
     Ok(file_path)
 }
 
-{% if template_type != "minimal" -%}
+{% if with_db -%}
 fn generate_simple_migration(name: &str, r#override: bool) -> Result<PathBuf, anyhow::Error> {
     let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
     let file_path = PathBuf::from(format!("./db/migrations/{}_{name}.sql", timestamp.as_secs()));
@@ -281,31 +312,37 @@ fn generate_simple_migration(name: &str, r#override: bool) -> Result<PathBuf, an
     Ok(file_path)
 }
 
-fn generate_migration(name: &str, r#override: bool) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+fn generate_migration(name: &str, up_sql: &str, down_sql: &str, r#override: bool) -> Result<(PathBuf, PathBuf), anyhow::Error> {
     let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
     let dir_path = PathBuf::from(&format!("./db/migrations/{}__{name}", timestamp.as_secs()));
     let up_migration = dir_path.join("up.sql");
     let down_migration = dir_path.join("down.sql");
 
     fs::create_dir_all(dir_path.as_path())?;
-    create_project_file(up_migration.to_str().expect("Invalid file path for migration!"), "".as_bytes(), r#override)?;
-    create_project_file(down_migration.to_str().expect("Invalid file path for migration!"), "".as_bytes(), r#override)?;
+    create_project_file(up_migration.to_str().expect("Invalid file path for migration!"), up_sql.as_bytes(), r#override)?;
+    create_project_file(down_migration.to_str().expect("Invalid file path for migration!"), down_sql.as_bytes(), r#override)?;
 
     Ok((up_migration, down_migration))
 }
 
 fn generate_entity(name: &str, fields: &[String], r#override: bool) -> Result<String, anyhow::Error> {
-    let fields = validate_fields(fields)?;
+    let fields = parse_fields(fields)?;
     let name = to_singular(name).to_lowercase();
     let name_plural = to_plural(&name);
     let struct_name = to_class_case(&name);
+    ensure_referenced_entities_exist(&fields, &name)?;
 
     let template = get_liquid_template("entity/file.rs")?;
     let variables = liquid::object!({
         "entity_struct_name": struct_name,
         "entity_singular_name": name,
         "entity_plural_name": name_plural,
-        "fields": fields,
+        "fields": field_variables(&fields, &struct_name, &name),
+        "enums": enum_variables(&fields, &struct_name, &name),
+        "references": reference_variables(&fields),
+        "imports": field_imports(&fields),
+        "lorem": fields.iter().any(Field::has_sentence_dummy),
+        "database": DATABASE,
     });
     let output = template
         .render(&variables)
@@ -325,6 +362,29 @@ fn generate_entity(name: &str, fields: &[String], r#override: bool) -> Result<St
     Ok(struct_name)
 }
 
+fn generate_entity_migration(name: &str, fields: &[String], r#override: bool) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+    let fields = parse_fields(fields)?;
+    let name = to_singular(name).to_lowercase();
+    let name_plural = to_plural(&name);
+    let struct_name = to_class_case(&name);
+
+    let variables = liquid::object!({
+        "table_name": name_plural,
+        "fields": field_variables(&fields, &struct_name, &name),
+        "enums": enum_variables(&fields, &struct_name, &name),
+        "foreign_keys": foreign_keys(&fields),
+        "database": DATABASE,
+    });
+    let up_sql = get_liquid_template("migration/create_table/up.sql")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+    let down_sql = get_liquid_template("migration/create_table/down.sql")?
+        .render(&variables)
+        .context("Failed to render Liquid template")?;
+
+    generate_migration(&format!("create_{name_plural}_table"), &up_sql, &down_sql, r#override)
+}
+
 fn generate_entity_test_helper(name: &str, r#override: bool) -> Result<String, anyhow::Error> {
     let name = to_singular(name).to_lowercase();
     let name_plural = to_plural(&name);
@@ -335,6 +395,7 @@ fn generate_entity_test_helper(name: &str, r#override: bool) -> Result<String, a
         "entity_struct_name": struct_name,
         "entity_singular_name": name,
         "entity_plural_name": name_plural,
+        "database": DATABASE,
     });
     let output = template
         .render(&variables)
@@ -354,7 +415,7 @@ fn generate_entity_test_helper(name: &str, r#override: bool) -> Result<String, a
     Ok(struct_name)
 }
 
-fn generate_crud_controller(name: &str, r#override: bool) -> Result<String, anyhow::Error> {
+fn generate_crud_controller(name: &str, parent: Option<&str>, r#override: bool) -> Result<String, anyhow::Error> {
     let name = to_snake_case(name).to_lowercase();
     let name_plural = to_plural(&name);
     let name_singular = to_singular(&name);
@@ -363,14 +424,19 @@ fn generate_crud_controller(name: &str, r#override: bool) -> Result<String, anyh
     let db_crate_name = to_snake_case(&db_crate_name);
     let macros_crate_name = get_member_package_name("macros")?;
     let macros_crate_name = to_snake_case(&macros_crate_name);
+    let entity = read_entity_module(&name_plural, &struct_name)?;
 
-    let template = get_liquid_template("controller/crud/controller.rs")?;
+    let template = match entity {
+        Some(_) => get_liquid_template("controller/entity/controller.rs")?,
+        None => get_liquid_template("controller/crud/controller.rs")?,
+    };
     let variables = liquid::object!({
         "entity_struct_name": struct_name,
         "entity_singular_name": name_singular,
         "entity_plural_name": name_plural,
         "db_crate_name": db_crate_name,
-        "macros_crate_name": macros_crate_name
+        "macros_crate_name": macros_crate_name,
+        "parent": parent_variables(parent, entity.as_ref(), &struct_name)?,
     });
     let output = template
         .render(&variables)
@@ -387,7 +453,7 @@ fn generate_crud_controller(name: &str, r#override: bool) -> Result<String, anyh
     Ok(file_path)
 }
 
-fn generate_crud_controller_test(name: &str, r#override: bool) -> Result<String, anyhow::Error> {
+fn generate_crud_controller_test(name: &str, parent: Option<&str>, auth: bool, r#override: bool) -> Result<String, anyhow::Error> {
     let name = to_snake_case(name).to_lowercase();
     let name_plural = to_plural(&name);
     let name_singular = to_singular(&name);
@@ -398,8 +464,12 @@ fn generate_crud_controller_test(name: &str, r#override: bool) -> Result<String,
     let macros_crate_name = to_snake_case(&macros_crate_name);
     let web_crate_name = get_member_package_name("web")?;
     let web_crate_name = to_snake_case(&web_crate_name);
+    let entity = read_entity_module(&name_plural, &struct_name)?;
 
-    let template = get_liquid_template("controller/crud/test.rs")?;
+    let template = match entity {
+        Some(_) => get_liquid_template("controller/entity/test.rs")?,
+        None => get_liquid_template("controller/crud/test.rs")?,
+    };
     let variables = liquid::object!({
         "entity_struct_name": struct_name,
         "entity_singular_name": name_singular,
@@ -407,6 +477,11 @@ fn generate_crud_controller_test(name: &str, r#override: bool) -> Result<String,
         "db_crate_name": db_crate_name,
         "macros_crate_name": macros_crate_name,
         "web_crate_name": web_crate_name,
+        "parent": parent_variables(parent, entity.as_ref(), &struct_name)?,
+        "references": entity.as_ref().map(EntityModule::reference_variables).unwrap_or_default(),
+        "invalid": entity.as_ref().and_then(EntityModule::invalid_variables),
+        "compare_field": entity.as_ref().and_then(EntityModule::compare_field).unwrap_or_default(),
+        "auth": auth,
     });
     let output = template
         .render(&variables)
@@ -418,6 +493,171 @@ fn generate_crud_controller_test(name: &str, r#override: bool) -> Result<String,
 
     Ok(file_path)
 }
+
+/// Builds the Liquid variables for the entity a CRUD controller's entity references – `nil` if there is none.
+fn parent_variables(parent: Option<&str>, entity: Option<&EntityModule>, entity_struct_name: &str) -> Result<Option<liquid::Object>, anyhow::Error> {
+    let Some(parent) = parent else {
+        return Ok(None);
+    };
+    let name_singular = to_singular(&to_snake_case(parent)).to_lowercase();
+    let optional = match entity {
+        Some(entity) if !entity.parents.contains(&name_singular) => {
+            return Err(anyhow!(
+                "The {entity_struct_name} entity has no load_all_for_{name_singular} function! Generate the entity with the field {name_singular}:references."
+            ))
+        }
+        Some(entity) => entity.is_optional(&format!("{name_singular}_id")),
+        None => false,
+    };
+
+    Ok(Some(liquid::object!({
+        "struct_name": to_class_case(&name_singular),
+        "singular_name": name_singular,
+        "plural_name": to_plural(&name_singular),
+        "optional": optional,
+    })))
+}
+
+/// The parts of an existing entity's module that a CRUD controller and its tests are generated from.
+struct EntityModule {
+    /// The fields of the entity's changeset
+    fields: Vec<ChangesetField>,
+    /// The singular names of the entities the entity references, e.g. `post` for its `load_all_for_post` function
+    parents: Vec<String>,
+}
+
+struct ChangesetField {
+    name: String,
+    rust_type: String,
+    min_length: Option<u32>,
+    max_length: Option<u32>,
+}
+
+impl EntityModule {
+    fn is_optional(&self, field_name: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.name == field_name && field.rust_type.starts_with("Option<"))
+    }
+
+    /// Builds the Liquid variables for the records that need to exist before a record of the entity can be created.
+    fn reference_variables(&self) -> Vec<liquid::Object> {
+        self.parents
+            .iter()
+            .map(|parent| {
+                let column = format!("{parent}_id");
+                liquid::object!({
+                    "struct_name": to_class_case(parent),
+                    "singular_name": parent,
+                    "plural_name": to_plural(parent),
+                    "optional": self.is_optional(&column),
+                    "column": column,
+                })
+            })
+            .collect()
+    }
+
+    /// Builds the Liquid variables for making a changeset invalid – a string field set to a value its validation rejects or, with an empty field, a payload that lacks the required fields. `nil` if neither is possible.
+    fn invalid_variables(&self) -> Option<liquid::Object> {
+        let invalid_string = self.fields.iter().filter(|field| field.rust_type == "String").find_map(|field| {
+            match (field.min_length, field.max_length) {
+                (Some(min), _) if min > 0 => Some((field.name.clone(), String::from("String::new()"))),
+                (_, Some(max)) => Some((field.name.clone(), format!(r#""a".repeat({})"#, max + 1))),
+                _ => None,
+            }
+        });
+        match invalid_string {
+            Some((field, value)) => Some(liquid::object!({ "field": field, "value": value })),
+            None if self.fields.iter().any(|field| !field.rust_type.starts_with("Option<")) => {
+                Some(liquid::object!({ "field": "", "value": "" }))
+            }
+            None => None,
+        }
+    }
+
+    /// The field the tests compare between changesets and entities – the first string field if there is one.
+    fn compare_field(&self) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|field| field.rust_type == "String")
+            .map(|field| field.name.clone())
+    }
+}
+
+/// Reads the changeset's fields and the entity's references from the entity's module in the `db` crate – `None` if the entity does not exist.
+fn read_entity_module(name_plural: &str, struct_name: &str) -> Result<Option<EntityModule>, anyhow::Error> {
+    let path = format!("./db/src/entities/{name_plural}.rs");
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+    let source = fs::read_to_string(&path).context(format!(r#"Could not read file "{path}"!"#))?;
+    let changeset = source
+        .split(&format!("pub struct {struct_name}Changeset "))
+        .nth(1)
+        .and_then(|rest| rest.split("\n}").next())
+        .ok_or_else(|| anyhow!("Could not find {struct_name}Changeset in {path}!"))?;
+
+    let field_re = Regex::new(r"^\s*pub (\w+): (.+),$")?;
+    let min_length_re = Regex::new(r"length\(.*min = (\d+)")?;
+    let max_length_re = Regex::new(r"length\(.*max = (\d+)")?;
+    let mut fields = Vec::new();
+    let (mut min_length, mut max_length) = (None, None);
+    for line in changeset.lines() {
+        if let Some(captures) = field_re.captures(line) {
+            fields.push(ChangesetField {
+                name: String::from(&captures[1]),
+                rust_type: String::from(&captures[2]),
+                min_length: min_length.take(),
+                max_length: max_length.take(),
+            });
+        } else if line.trim_start().starts_with("#[validate(") {
+            min_length = min_length_re.captures(line).and_then(|captures| captures[1].parse().ok());
+            max_length = max_length_re.captures(line).and_then(|captures| captures[1].parse().ok());
+        }
+    }
+
+    let parent_re = Regex::new(r"pub async fn load_all_for_(\w+)\(")?;
+    let parents = parent_re
+        .captures_iter(&source)
+        .map(|captures| String::from(&captures[1]))
+        .collect();
+
+    Ok(Some(EntityModule { fields, parents }))
+}
+
+/// The routes of a CRUD controller's actions – those writing data are protected by the auth middleware if `auth` is set.
+fn crud_routes(name: &str, parent: Option<&str>, has_batch: bool, auth: bool) -> Vec<Route> {
+    let name = to_snake_case(name).to_lowercase();
+    let name_plural = to_plural(&name);
+    let mut routes = vec![Route::new("post", format!("/{name_plural}"), format!("{name}::create"), auth)];
+    if has_batch {
+        routes.push(Route::new("put", format!("/{name_plural}"), format!("{name}::create_batch"), auth));
+    }
+    routes.extend([
+        Route::new("delete", format!("/{name_plural}/{ID_SEGMENT}"), format!("{name}::delete"), auth),
+        Route::new("put", format!("/{name_plural}/{ID_SEGMENT}"), format!("{name}::update"), auth),
+        Route::new("get", format!("/{name_plural}"), format!("{name}::read_all"), false),
+        Route::new("get", format!("/{name_plural}/{ID_SEGMENT}"), format!("{name}::read_one"), false),
+    ]);
+    if let Some(parent) = parent {
+        let parent = to_singular(&to_snake_case(parent)).to_lowercase();
+        routes.push(Route::new(
+            "get",
+            format!("/{}/{ID_SEGMENT}/{name_plural}", to_plural(&parent)),
+            format!("{name}::read_all_for_{parent}"),
+            false,
+        ));
+    }
+    routes
+}
+
+fn ensure_auth() -> Result<(), anyhow::Error> {
+    if Path::new("./web/src/middlewares/auth.rs").exists() && Path::new("./db/src/test_helpers/users.rs").exists() {
+        Ok(())
+    } else {
+        Err(anyhow!("The project does not use authentication – --auth requires the auth middleware in ./web/src/middlewares/auth.rs!"))
+    }
+}
 {% endif -%}
 
 fn get_liquid_template(path: &str) -> Result<Template, anyhow::Error> {
@@ -479,10 +719,339 @@ fn append_module_definition_to_project_file(path: &str, module_name: &str, is_pu
     Ok(())
 }
 
+/// The file the application's routes are defined in.
+const ROUTES_FILE: &str = "./web/src/routes.rs";
+
+/// The path segment for a record's id – a constant as the braces would otherwise need escaping in format strings which Liquid would take for a tag.
+const ID_SEGMENT: &str = "{id}";
+
+/// A route for a controller's action, registered in `init_routes` in [`ROUTES_FILE`].
+struct Route {
+    /// The routing function for the HTTP method, e.g. `get`
+    method: &'static str,
+    path: String,
+    /// The action relative to the controllers module, e.g. `tasks::read_all`
+    handler: String,
+    /// Whether the route is placed behind the auth middleware's `route_layer`
+    protected: bool,
+}
+
+impl Route {
+    fn new(method: &'static str, path: String, handler: String, protected: bool) -> Route {
+        Route {
+            method,
+            path,
+            handler,
+            protected,
+        }
+    }
+
+    fn call(&self) -> String {
+        format!(r#".route("{}", {}({}))"#, self.path, self.method, self.handler)
+    }
+
+    /// Whether the method call routes the same method and path, in which case axum would panic if the route was added again.
+    fn is_routed_by(&self, call: &ExprMethodCall) -> bool {
+        if call.method != "route" || call.args.len() != 2 {
+            return false;
+        }
+        let same_path = matches!(&call.args[0], Expr::Lit(expr) if matches!(&expr.lit, Lit::Str(path) if path.value() == self.path));
+        let same_method = matches!(&call.args[1], Expr::Call(expr) if matches!(&*expr.func, Expr::Path(func) if func.path.is_ident(self.method)));
+        same_path && same_method
+    }
+}
+
+impl std::fmt::Display for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} to {}", self.method.to_uppercase(), self.path, self.handler)
+    }
+}
+
+fn controller_routes(name: &str) -> Vec<Route> {
+    let name = to_snake_case(name).to_lowercase();
+    vec![Route::new("get", format!("/{name}/action"), format!("{name}::action"), false)]
+}
+
+/// Registers a controller's routes, asking to route the actions manually if that fails.
+fn route_actions(ui: &mut UI<'_>, controller: &str, routes: Vec<Route>) {
+    let controller = to_snake_case(controller).to_lowercase();
+    match register_routes(&controller, routes) {
+        Ok((routed, skipped)) => {
+            for route in routed {
+                ui.success(&format!("Routed {route}."));
+            }
+            for route in skipped {
+                ui.info(&format!("Skipped routing {route} as its method and path are routed in {ROUTES_FILE} already."));
+            }
+        }
+        Err(e) => {
+            ui.warning(&format!("Could not route the controller's actions: {e}"));
+            ui.info(&format!("Do not forget to route the controller's actions in {ROUTES_FILE}!"));
+        }
+    }
+}
+
+/// Adds the routes that do not exist yet to the router in `init_routes` along with the imports they need, returning the added and the skipped routes.
+///
+/// The file is parsed with syn to find the router's method chain. The new calls are inserted into the source at the positions of the chain's calls so that the file's formatting and comments are preserved. Protected routes are added before the auth middleware's `route_layer` which is added if the router does not have it yet, all other routes before `with_state`.
+fn register_routes(controller: &str, routes: Vec<Route>) -> Result<(Vec<Route>, Vec<Route>), anyhow::Error> {
+    let source = fs::read_to_string(ROUTES_FILE).context(format!(r#"Could not read file "{ROUTES_FILE}"!"#))?;
+    let file = syn::parse_file(&source).context(format!(r#"Could not parse file "{ROUTES_FILE}"!"#))?;
+    let init_routes = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Fn(function) if function.sig.ident == "init_routes" => Some(function),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("Could not find the init_routes function in {ROUTES_FILE}!"))?;
+    let mut finder = WithStateFinder::default();
+    finder.visit_item_fn(init_routes);
+    let with_state = finder
+        .with_state
+        .ok_or_else(|| anyhow!("Could not find the router's with_state call in {ROUTES_FILE}!"))?;
+    let chain = method_call_chain(with_state);
+
+    let (skipped, routes): (Vec<Route>, Vec<Route>) = routes
+        .into_iter()
+        .partition(|route| chain.iter().any(|call| route.is_routed_by(call)));
+    if routes.is_empty() {
+        return Ok((routes, skipped));
+    }
+
+    let mut imports = vec![format!("crate::controllers::{controller}")];
+    imports.extend(routes.iter().map(|route| format!("axum::routing::{}", route.method)));
+    let mut insertions = Vec::new();
+    let protected: Vec<String> = routes.iter().filter(|route| route.protected).map(Route::call).collect();
+    let public: Vec<String> = routes.iter().filter(|route| !route.protected).map(Route::call).collect();
+    if !protected.is_empty() {
+        match chain.iter().find(|call| is_auth_layer(call)) {
+            Some(auth_layer) => insertions.push(insert_calls(&source, auth_layer, &protected)),
+            None => {
+                // route layers only apply to the routes added before them so the layer goes to the start of the chain
+                let state = with_state.args.first().map_or("shared_app_state", |state| source_of(&source, state));
+                let mut calls = protected;
+                calls.push(format!(".route_layer(middleware::from_fn_with_state({state}.clone(), auth))"));
+                insertions.push(insert_calls(&source, chain[0], &calls));
+                imports.push(String::from("axum::middleware"));
+                imports.push(String::from("crate::middlewares::auth::auth"));
+            }
+        }
+    }
+    if !public.is_empty() {
+        insertions.push(insert_calls(&source, with_state, &public));
+    }
+    insertions.extend(insert_imports(&source, &file, &imports));
+
+    // inserting from the end keeps the offsets of the remaining insertions valid – the sort is stable so that insertions at the same offset keep their order
+    insertions.sort_by_key(|(offset, _)| *offset);
+    let mut source = source;
+    for (offset, text) in insertions.iter().rev() {
+        source.insert_str(*offset, text);
+    }
+    fs::write(ROUTES_FILE, source).context(format!(r#"Could not write file "{ROUTES_FILE}"!"#))?;
+
+    Ok((routes, skipped))
+}
+
+/// Finds the router's `with_state` call which ends the chain of method calls that define the routes.
+#[derive(Default)]
+struct WithStateFinder<'ast> {
+    with_state: Option<&'ast ExprMethodCall>,
+}
+
+impl<'ast> Visit<'ast> for WithStateFinder<'ast> {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if self.with_state.is_none() && call.method == "with_state" {
+            self.with_state = Some(call);
+        } else {
+            syn::visit::visit_expr_method_call(self, call);
+        }
+    }
+}
+
+/// The method calls of a chain in the order they are called, ending with the given call.
+fn method_call_chain(last: &ExprMethodCall) -> Vec<&ExprMethodCall> {
+    let mut chain = vec![last];
+    let mut receiver = &*last.receiver;
+    while let Expr::MethodCall(call) = receiver {
+        chain.push(call);
+        receiver = &call.receiver;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Whether the method call is a `route_layer` call with the `auth` middleware, e.g. `.route_layer(middleware::from_fn_with_state(shared_app_state.clone(), auth))`.
+fn is_auth_layer(call: &ExprMethodCall) -> bool {
+    call.method == "route_layer"
+        && call.args.iter().any(|arg| match arg {
+            Expr::Call(layer) => layer
+                .args
+                .iter()
+                .any(|arg| matches!(arg, Expr::Path(middleware) if middleware.path.is_ident("auth"))),
+            _ => false,
+        })
+}
+
+/// The insertion of the calls before the given method call, each on its own line.
+fn insert_calls(source: &str, before: &ExprMethodCall, calls: &[String]) -> (usize, String) {
+    let offset = byte_offset(source, before.dot_token.span.start());
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let prefix = &source[line_start..offset];
+    if prefix.trim().is_empty() {
+        // the call starts its own line, e.g. `        .with_state(shared_app_state)`
+        let text = calls.iter().map(|call| format!("{call}\n{prefix}")).collect();
+        (offset, text)
+    } else {
+        // the call follows its receiver, e.g. `    Router::new().with_state(shared_app_state)`
+        let indentation = format!("{}    ", &prefix[..prefix.len() - prefix.trim_start().len()]);
+        let mut text: String = calls.iter().map(|call| format!("\n{indentation}{call}")).collect();
+        text.push_str(&format!("\n{indentation}"));
+        (offset, text)
+    }
+}
+
+/// The insertions of `use` items for the paths that are not imported yet, each placed among the file's `use` items in the order rustfmt sorts them in.
+fn insert_imports(source: &str, file: &syn::File, paths: &[String]) -> Vec<(usize, String)> {
+    let mut imported = Vec::new();
+    for item in &file.items {
+        if let Item::Use(item) = item {
+            collect_imported_paths(String::new(), &item.tree, &mut imported);
+        }
+    }
+    let mut missing: Vec<&String> = paths
+        .iter()
+        .filter(|path| {
+            let glob = path.rsplit_once("::").map(|(parent, _)| format!("{parent}::*"));
+            !imported.contains(path) && glob.is_none_or(|glob| !imported.contains(&glob))
+        })
+        .collect();
+    missing.sort();
+    missing.dedup();
+    if missing.is_empty() {
+        return Vec::new();
+    }
+
+    let routing_functions: Vec<&str> = missing
+        .iter()
+        .filter_map(|path| path.strip_prefix("axum::routing::"))
+        .collect();
+    let mut items: Vec<String> = missing
+        .iter()
+        .filter(|path| !path.starts_with("axum::routing::"))
+        .map(|path| format!("use {path};"))
+        .collect();
+    match routing_functions.as_slice() {
+        [] => {}
+        [function] => items.push(format!("use axum::routing::{function};")),
+        functions => {
+            // the group is built without a format string as Liquid would take its escaped braces for a tag
+            let mut item = String::from("use axum::routing::{");
+            item.push_str(&functions.join(", "));
+            item.push_str("};");
+            items.push(item);
+        }
+    }
+
+    let uses: Vec<&syn::ItemUse> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(item) => Some(item),
+            _ => None,
+        })
+        .collect();
+    let mut keyed_items: Vec<(Vec<(u8, String)>, String)> = items
+        .into_iter()
+        .map(|item| {
+            let key = syn::parse_str::<syn::ItemUse>(&item).map(|parsed| use_sort_key(&parsed.tree)).unwrap_or_default();
+            (key, item)
+        })
+        .collect();
+    // items inserted at the same offset keep their order so they must be sorted among themselves as well
+    keyed_items.sort();
+    let mut insertions = Vec::new();
+    for (key, item) in keyed_items {
+        let insertion = match uses.iter().find(|existing| use_sort_key(&existing.tree) > key) {
+            Some(next) => (byte_offset(source, next.span().start()), format!("{item}\n")),
+            None => match uses.last() {
+                Some(last) => (byte_offset(source, last.span().end()), format!("\n{item}")),
+                None => (0, format!("{item}\n")),
+            },
+        };
+        insertions.push(insertion);
+    }
+    insertions
+}
+
+/// The key rustfmt sorts `use` items by: `self`, `super`, and `crate` come first, lowercase names before capitalized ones, and globs and groups last.
+fn use_sort_key(tree: &UseTree) -> Vec<(u8, String)> {
+    let segment = |ident: &syn::Ident| {
+        let name = ident.to_string();
+        let rank = match name.as_str() {
+            "self" => 0,
+            "super" => 1,
+            "crate" => 2,
+            name if name.starts_with(char::is_uppercase) => 4,
+            _ => 3,
+        };
+        (rank, name)
+    };
+    match tree {
+        UseTree::Path(path) => {
+            let mut key = vec![segment(&path.ident)];
+            key.extend(use_sort_key(&path.tree));
+            key
+        }
+        UseTree::Name(name) => vec![segment(&name.ident)],
+        UseTree::Rename(rename) => vec![segment(&rename.ident)],
+        UseTree::Glob(_) => vec![(5, String::from("*"))],
+        UseTree::Group(_) => vec![(6, String::new())],
+    }
+}
+
+/// Collects the paths a `use` tree imports, e.g. `axum::routing::get` for `use axum::routing::{get, post};`.
+fn collect_imported_paths(prefix: String, tree: &UseTree, paths: &mut Vec<String>) {
+    match tree {
+        UseTree::Path(path) => collect_imported_paths(format!("{prefix}{}::", path.ident), &path.tree, paths),
+        UseTree::Name(name) if name.ident == "self" => paths.push(prefix.trim_end_matches("::").to_string()),
+        UseTree::Name(name) => paths.push(format!("{prefix}{}", name.ident)),
+        UseTree::Rename(rename) => paths.push(format!("{prefix}{}", rename.ident)),
+        UseTree::Glob(_) => paths.push(format!("{prefix}*")),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_imported_paths(prefix.clone(), tree, paths);
+            }
+        }
+    }
+}
+
+/// The source code of a syntax tree node.
+fn source_of<'a>(source: &'a str, node: &impl Spanned) -> &'a str {
+    let span = node.span();
+    &source[byte_offset(source, span.start())..byte_offset(source, span.end())]
+}
+
+/// Converts a line and column as reported by syn into a byte offset into the source.
+fn byte_offset(source: &str, location: LineColumn) -> usize {
+    let line_start: usize = source.split_inclusive('\n').take(location.line - 1).map(str::len).sum();
+    let column: usize = source[line_start..].chars().take(location.column).map(char::len_utf8).sum();
+    line_start + column
+}
+
 fn has_db() -> bool {
     get_member_package_name("db").is_ok()
 }
 
+{% if with_db -%}
+fn has_entity(name: &str) -> bool {
+    let name_plural = to_plural(&to_snake_case(name).to_lowercase());
+    Path::new(&format!("./db/src/entities/{name_plural}.rs")).exists()
+}
+
+{% endif -%}
+
 fn get_member_package_name(path: &str) -> Result<String, anyhow::Error> {
     let mut cmd = MetadataCommand::new();
     let package_graph = PackageGraph::from_command(cmd.manifest_path("./Cargo.toml"))?;
@@ -496,38 +1065,520 @@ fn get_member_package_name(path: &str) -> Result<String, anyhow::Error> {
     Err(anyhow!("Could not find workspace member at path: {path}"))
 }
 
-{% if template_type != "minimal" -%}
-fn validate_fields(fields: &[String]) -> Result<Vec<HashMap<String, String>>, anyhow::Error> {
-    let re =
-        Regex::new(r"^([a-zA-Z][a-zA-Z0-9_]+):(bool|Bool|i8|i16|i32|i64|f32|f64|String|string)$")?;
-    let mut mapped_fields = Vec::<HashMap<String, String>>::new();
+{% if with_db -%}
+/// The types supported in field definitions of `cargo generate entity`.
+const SUPPORTED_FIELD_TYPES: &str = "bool, i8, i16, i32, i64, f32, f64, String, Uuid, DateTime<Utc>, {% unless database == "sqlite" %}Decimal, {% endunless %}Vec<u8>, serde_json::Value, enum=<variant>,<variant>,…, and references, each optionally wrapped in Option<…>";
+
+/// A field of an entity as defined on the command line, e.g. `title:String:max=100:unique`.
+struct Field {
+    name: String,
+    field_type: FieldType,
+    optional: bool,
+    unique: bool,
+    index: bool,
+    /// The default value as an SQL literal
+    default: Option<String>,
+    min: Option<String>,
+    max: Option<String>,
+    /// The `ON DELETE` action of a reference's foreign key
+    on_delete: Option<String>,
+}
+
+enum FieldType {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    String,
+    Uuid,
+    DateTime,
+    {%- unless database == "sqlite" %}
+    Decimal,
+    {%- endunless %}
+    Bytes,
+    Json,
+    /// An enum with the given variants in snake case
+    Enum(Vec<String>),
+    /// A reference to the entity with the given singular name, e.g. `post` for `post:references`
+    Reference(String),
+}
+
+impl FieldType {
+    fn parse(definition: &str) -> Option<FieldType> {
+        if let Some(variants) = definition.strip_prefix("enum=") {
+            let variants: Vec<String> = variants.split(',').map(String::from).collect();
+            let valid = variants.iter().all(|variant| {
+                variant.starts_with(|c: char| c.is_ascii_lowercase())
+                    && variant.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            });
+            return valid.then_some(FieldType::Enum(variants));
+        }
+
+        match definition {
+            "bool" | "Bool" => Some(FieldType::Bool),
+            "i8" => Some(FieldType::I8),
+            "i16" => Some(FieldType::I16),
+            "i32" => Some(FieldType::I32),
+            "i64" => Some(FieldType::I64),
+            "f32" => Some(FieldType::F32),
+            "f64" => Some(FieldType::F64),
+            "String" | "string" => Some(FieldType::String),
+            "Uuid" => Some(FieldType::Uuid),
+            "DateTime<Utc>" | "DateTime" => Some(FieldType::DateTime),
+            {%- unless database == "sqlite" %}
+            "Decimal" => Some(FieldType::Decimal),
+            {%- endunless %}
+            "Vec<u8>" => Some(FieldType::Bytes),
+            "serde_json::Value" | "Json" => Some(FieldType::Json),
+            _ => None,
+        }
+    }
+
+    fn rust_type(&self, enum_name: &str) -> String {
+        String::from(match self {
+            FieldType::Bool => "bool",
+            FieldType::I8 => "i8",
+            FieldType::I16 => "i16",
+            FieldType::I32 => "i32",
+            FieldType::I64 => "i64",
+            FieldType::F32 => "f32",
+            FieldType::F64 => "f64",
+            FieldType::String => "String",
+            FieldType::Uuid => "Uuid",
+            FieldType::DateTime => "DateTime<Utc>",
+            {%- unless database == "sqlite" %}
+            FieldType::Decimal => "Decimal",
+            {%- endunless %}
+            FieldType::Bytes => "Vec<u8>",
+            FieldType::Json => "serde_json::Value",
+            FieldType::Enum(_) => enum_name,
+            FieldType::Reference(_) => "Uuid",
+        })
+    }
+
+    /// Whether sqlx infers a different Rust type for the column than the field's so that queries need to override it.
+    fn needs_type_override(&self) -> bool {
+        {%- if database == "sqlite" %}
+        !matches!(self, FieldType::Bool | FieldType::I32 | FieldType::I64 | FieldType::F64 | FieldType::String | FieldType::Bytes)
+        {%- elsif database == "mysql" %}
+        matches!(self, FieldType::Uuid | FieldType::Enum(_) | FieldType::Reference(_))
+        {%- else %}
+        matches!(self, FieldType::Enum(_))
+        {%- endif %}
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(self, FieldType::I8 | FieldType::I16 | FieldType::I32 | FieldType::I64)
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self, FieldType::F32 | FieldType::F64)
+    }
+}
+
+fn parse_fields(fields: &[String]) -> Result<Vec<Field>, anyhow::Error> {
+    let name_re = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_]+$")?;
+    let mut parsed_fields = Vec::<Field>::new();
+    for definition in fields {
+        let field = parse_field(definition.trim(), &name_re)?;
+        if parsed_fields.iter().any(|parsed_field| parsed_field.name == field.name) {
+            return Err(anyhow!("Duplicate field: {}!", field.name));
+        }
+        parsed_fields.push(field);
+    }
+
+    Ok(parsed_fields)
+}
+
+fn parse_field(definition: &str, name_re: &Regex) -> Result<Field, anyhow::Error> {
+    // the path separator would otherwise be taken for separators of the definition's parts
+    let normalized = definition.replace("serde_json::Value", "Json");
+    let mut parts = normalized.split(':');
+    let name = parts
+        .next()
+        .filter(|name| name_re.is_match(name))
+        .ok_or_else(|| anyhow!("Invalid field definition: {definition}!"))?;
+    let type_definition = parts
+        .next()
+        .ok_or_else(|| anyhow!("Invalid field definition: {definition}! Fields are defined as <name>:<type>[:<modifier>…]."))?;
+    let (type_definition, optional) = match type_definition.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')) {
+        Some(inner) => (inner, true),
+        None => (type_definition, false),
+    };
+    let (name, field_type) = if type_definition == "references" {
+        // the field is named after the referenced entity, e.g. `post_id` for `post:references`
+        let parent = to_singular(&to_snake_case(name.strip_suffix("_id").unwrap_or(name))).to_lowercase();
+        (format!("{parent}_id"), FieldType::Reference(parent))
+    } else {
+        let field_type = FieldType::parse(type_definition).ok_or_else(|| {
+            anyhow!(r#"Unsupported type "{type_definition}" in field definition: {definition}! Supported types are {SUPPORTED_FIELD_TYPES}."#)
+        })?;
+        (String::from(name), field_type)
+    };
+
+    let mut field = Field {
+        name,
+        field_type,
+        optional,
+        unique: false,
+        index: false,
+        default: None,
+        min: None,
+        max: None,
+        on_delete: None,
+    };
+    for modifier in parts {
+        match modifier.split_once('=') {
+            None if modifier == "unique" => field.unique = true,
+            None if modifier == "index" => field.index = true,
+            Some(("default", value)) => field.default = Some(default_literal(&field.field_type, value, definition)?),
+            Some(("min", value)) => field.min = Some(bound(&field.field_type, value, definition)?),
+            Some(("max", value)) => field.max = Some(bound(&field.field_type, value, definition)?),
+            Some(("on_delete", value)) => field.on_delete = Some(on_delete_action(&field, value, definition)?),
+            _ => {
+                return Err(anyhow!(
+                    r#"Unknown modifier "{modifier}" in field definition: {definition}! Supported modifiers are unique, index, default=<value>, min=<value>, max=<value>, and on_delete=<action> for references."#
+                ))
+            }
+        }
+    }
+
+    Ok(field)
+}
+
+/// Validates a `min` or `max` bound – a length for strings and byte arrays, a value for numbers.
+fn bound(field_type: &FieldType, value: &str, definition: &str) -> Result<String, anyhow::Error> {
+    let valid = match field_type {
+        FieldType::String | FieldType::Bytes => value.parse::<u32>().is_ok(),
+        field_type if field_type.is_integer() => value.parse::<i64>().is_ok(),
+        field_type if field_type.is_float() => value.parse::<f64>().is_ok(),
+        _ => {
+            return Err(anyhow!(
+                "min and max are only supported for strings, byte arrays, and numbers: {definition}!"
+            ));
+        }
+    };
+    if !valid {
+        return Err(anyhow!(r#"Invalid bound "{value}" in field definition: {definition}!"#));
+    }
+
+    if field_type.is_float() && !value.contains('.') {
+        Ok(format!("{value}.0"))
+    } else {
+        Ok(String::from(value))
+    }
+}
+
+/// Converts a default value into an SQL literal for the column.
+fn default_literal(field_type: &FieldType, value: &str, definition: &str) -> Result<String, anyhow::Error> {
+    let invalid = || anyhow!(r#"Invalid default value "{value}" in field definition: {definition}!"#);
+    match field_type {
+        FieldType::String => Ok(quote_sql(value)),
+        FieldType::Enum(variants) if variants.iter().any(|variant| variant == value) => Ok(quote_sql(value)),
+        FieldType::Bool => match value {
+            {%- if database == "postgres" %}
+            "true" | "false" => Ok(String::from(value)),
+            {%- else %}
+            "true" | "false" => Ok(value.to_uppercase()),
+            {%- endif %}
+            _ => Err(invalid()),
+        },
+        FieldType::DateTime if value == "now" => Ok(String::from("CURRENT_TIMESTAMP")),
+        {%- unless database == "sqlite" %}
+        FieldType::Decimal => value.parse::<f64>().map(|_| String::from(value)).map_err(|_| invalid()),
+        {%- endunless %}
+        field_type if field_type.is_integer() => value.parse::<i64>().map(|_| String::from(value)).map_err(|_| invalid()),
+        field_type if field_type.is_float() => value.parse::<f64>().map(|_| String::from(value)).map_err(|_| invalid()),
+        FieldType::DateTime => Err(anyhow!(r#"Only "now" is supported as default value for DateTime fields: {definition}!"#)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Converts the `on_delete` modifier of a reference into the foreign key's `ON DELETE` action.
+fn on_delete_action(field: &Field, value: &str, definition: &str) -> Result<String, anyhow::Error> {
+    if !matches!(field.field_type, FieldType::Reference(_)) {
+        return Err(anyhow!("on_delete is only supported for references: {definition}!"));
+    }
+    match value {
+        "cascade" => Ok(String::from("CASCADE")),
+        "restrict" => Ok(String::from("RESTRICT")),
+        "set_null" if field.optional => Ok(String::from("SET NULL")),
+        "set_null" => Err(anyhow!("on_delete=set_null requires an optional reference, e.g. 'post:Option<references>:on_delete=set_null': {definition}!")),
+        _ => Err(anyhow!(r#"Invalid on_delete action "{value}" in field definition: {definition}! Supported actions are cascade, restrict, and set_null."#)),
+    }
+}
+
+fn quote_sql(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+{% unless database == "postgres" -%}
+fn quote_variants(variants: &[String]) -> String {
+    variants.iter().map(|variant| quote_sql(variant)).collect::<Vec<_>>().join(", ")
+}
+
+{% endunless -%}
+impl Field {
+    /// The name of the Rust enum generated for enum fields, e.g. `PostStatus` for the `status` field of the `Post` entity.
+    ///
+    /// Unlike entity names, field names are not singularized – `to_class_case` would turn `status` into `Statu`.
+    fn enum_name(&self, entity_struct_name: &str) -> String {
+        format!("{entity_struct_name}{}", to_pascal_case(&self.name))
+    }
+
+    fn rust_type(&self, entity_struct_name: &str) -> String {
+        let rust_type = self.field_type.rust_type(&self.enum_name(entity_struct_name));
+        if self.optional {
+            format!("Option<{rust_type}>")
+        } else {
+            rust_type
+        }
+    }
+
+    /// The column type that sqlx maps back to the field's Rust type, possibly with a type override – see [`FieldType::needs_type_override`].
+    fn column_type(&self, {% unless database == "postgres" %}_{% endunless %}entity_singular_name: &str) -> String {
+        match &self.field_type {
+            {%- if database == "sqlite" %}
+            FieldType::Bool => String::from("BOOLEAN"),
+            FieldType::I8 | FieldType::I16 | FieldType::I32 => String::from("INT4"),
+            FieldType::I64 => String::from("INTEGER"),
+            FieldType::F32 | FieldType::F64 => String::from("REAL"),
+            FieldType::String | FieldType::Json => String::from("TEXT"),
+            FieldType::Uuid | FieldType::Reference(_) | FieldType::Bytes => String::from("BLOB"),
+            FieldType::DateTime => String::from("DATETIME"),
+            FieldType::Enum(variants) => format!("TEXT CHECK ({} IN ({}))", self.name, quote_variants(variants)),
+            {%- elsif database == "mysql" %}
+            FieldType::Bool => String::from("BOOLEAN"),
+            FieldType::I8 => String::from("TINYINT"),
+            FieldType::I16 => String::from("SMALLINT"),
+            FieldType::I32 => String::from("INT"),
+            FieldType::I64 => String::from("BIGINT"),
+            FieldType::F32 => String::from("FLOAT"),
+            FieldType::F64 => String::from("DOUBLE"),
+            FieldType::String => format!("VARCHAR({})", self.max.as_deref().unwrap_or("255")),
+            FieldType::Uuid | FieldType::Reference(_) => String::from("BINARY(16)"),
+            FieldType::DateTime => String::from("TIMESTAMP"),
+            FieldType::Decimal => String::from("DECIMAL(19, 4)"),
+            FieldType::Bytes => String::from("BLOB"),
+            FieldType::Json => String::from("JSON"),
+            FieldType::Enum(variants) => format!("ENUM({})", quote_variants(variants)),
+            {%- else %}
+            FieldType::Bool => String::from("boolean"),
+            // sqlx maps i8 to Postgres' single-byte "char" type
+            FieldType::I8 => String::from("\"char\""),
+            FieldType::I16 => String::from("smallint"),
+            FieldType::I32 => String::from("integer"),
+            FieldType::I64 => String::from("bigint"),
+            FieldType::F32 => String::from("real"),
+            FieldType::F64 => String::from("double precision"),
+            FieldType::String => format!("varchar({})", self.max.as_deref().unwrap_or("255")),
+            FieldType::Uuid | FieldType::Reference(_) => String::from("uuid"),
+            FieldType::DateTime => String::from("timestamptz"),
+            FieldType::Decimal => String::from("numeric"),
+            FieldType::Bytes => String::from("bytea"),
+            FieldType::Json => String::from("jsonb"),
+            // the enum's type is created in the migration
+            FieldType::Enum(_) => format!("{entity_singular_name}_{}", self.name),
+            {%- endif %}
+        }
+    }
+
+    /// The column's definition in the `CREATE TABLE` statement.
+    fn column_definition(&self, entity_singular_name: &str) -> String {
+        let mut definition = format!("{} {}", self.name, self.column_type(entity_singular_name));
+        if !self.optional {
+            definition.push_str(" NOT NULL");
+        }
+        if self.unique {
+            definition.push_str(" UNIQUE");
+        }
+        if let Some(default) = &self.default {
+            definition.push_str(&format!(" DEFAULT {default}"));
+        }
+        definition
+    }
+
+    /// The table constraint for a reference's foreign key – required references are deleted along with the referenced record, optional ones are set to `NULL` unless the `on_delete` modifier says otherwise.
+    fn foreign_key(&self) -> Option<String> {
+        match &self.field_type {
+            FieldType::Reference(parent) => {
+                let on_delete = self.on_delete.as_deref().unwrap_or(if self.optional { "SET NULL" } else { "CASCADE" });
+                Some(format!("FOREIGN KEY ({}) REFERENCES {} (id) ON DELETE {on_delete}", self.name, to_plural(parent)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The expression selecting the column, overriding the type sqlx infers where necessary.
+    fn select(&self, entity_struct_name: &str) -> String {
+        if self.field_type.needs_type_override() {
+            let rust_type = self.field_type.rust_type(&self.enum_name(entity_struct_name));
+            format!(r#"{} AS "{}: {rust_type}""#, self.name, self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// The changeset field's `#[validate(…)]` arguments if any – string lengths are limited to the column's length.
+    fn validation(&self) -> Option<String> {
+        let bounds = [("min", &self.min), ("max", &self.max)]
+            .into_iter()
+            .filter_map(|(bound, value)| value.as_ref().map(|value| format!("{bound} = {value}")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.field_type {
+            FieldType::String if bounds.is_empty() => Some(String::from("length(max = 255)")),
+            FieldType::String | FieldType::Bytes if !bounds.is_empty() => Some(format!("length({bounds})")),
+            _ if !bounds.is_empty() => Some(format!("range({bounds})")),
+            _ => None,
+        }
+    }
+
+    /// Whether fake values for the field are lorem ipsum sentences – sentences of 3 to 7 words are between 6 and 105 characters long so other lengths get random characters instead.
+    fn has_sentence_dummy(&self) -> bool {
+        let min: Option<u32> = self.min.as_ref().and_then(|min| min.parse().ok());
+        let max: Option<u32> = self.max.as_ref().and_then(|max| max.parse().ok());
+        matches!(self.field_type, FieldType::String) && min.unwrap_or(0) <= 6 && max.unwrap_or(255) >= 105
+    }
+
+    /// The changeset field's `#[dummy(…)]` arguments for generating fake data if the type's default is not suitable.
+    fn dummy(&self) -> Option<String> {
+        match self.field_type {
+            FieldType::String if self.has_sentence_dummy() => Some(String::from(r#"faker = "Sentence(3..8)""#)),
+            FieldType::String => {
+                let max: Option<u32> = self.max.as_ref().and_then(|max| max.parse().ok());
+                let min: u32 = self.min.as_ref().and_then(|min| min.parse().ok()).unwrap_or(max.map_or(1, |max| max.min(1)));
+                let max = max.unwrap_or(min + 100);
+                Some(format!(r#"faker = "{min}..={max}""#))
+            }
+            FieldType::Uuid | FieldType::Reference(_) => Some(String::from(r#"faker = "fake::uuid::UUIDv4""#)),
+            FieldType::DateTime => Some(String::from(r#"faker = "fake::faker::chrono::en::DateTime()""#)),
+            FieldType::Json => Some(String::from("default")),
+            FieldType::Bytes if self.min.is_some() || self.max.is_some() => {
+                let min: u32 = self.min.as_ref().and_then(|min| min.parse().ok()).unwrap_or(0);
+                let max: u32 = self.max.as_ref().and_then(|max| max.parse().ok()).unwrap_or(min + 100);
+                Some(format!(r#"faker = "(fake::Faker, {min}..={max})""#))
+            }
+            ref field_type if field_type.is_integer() && (self.min.is_some() || self.max.is_some()) => {
+                let min: i64 = self.min.as_ref().and_then(|min| min.parse().ok()).unwrap_or(0);
+                let max: i64 = self.max.as_ref().and_then(|max| max.parse().ok()).unwrap_or(min + 100);
+                Some(format!(r#"faker = "{min}..={max}""#))
+            }
+            ref field_type if field_type.is_float() && (self.min.is_some() || self.max.is_some()) => {
+                let min: f64 = self.min.as_ref().and_then(|min| min.parse().ok()).unwrap_or(0.0);
+                let max: f64 = self.max.as_ref().and_then(|max| max.parse().ok()).unwrap_or(min + 100.0);
+                Some(format!(r#"faker = "{min:?}..={max:?}""#))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Builds the Liquid variables for the fields of an entity as used by the entity and migration templates.
+fn field_variables(fields: &[Field], entity_struct_name: &str, entity_singular_name: &str) -> Vec<HashMap<String, String>> {
+    fields
+        .iter()
+        .map(|field| {
+            let mut variables = HashMap::new();
+            variables.insert(String::from("name"), field.name.clone());
+            variables.insert(String::from("type"), field.rust_type(entity_struct_name));
+            variables.insert(String::from("select"), field.select(entity_struct_name));
+            variables.insert(String::from("column"), field.column_definition(entity_singular_name));
+            // foreign keys are indexed so that loading the records referencing a record is fast
+            let index = field.index || matches!(field.field_type, FieldType::Reference(_));
+            variables.insert(String::from("index"), index.to_string());
+            variables.insert(String::from("validate"), field.validation().unwrap_or_default());
+            variables.insert(String::from("dummy"), field.dummy().unwrap_or_default());
+            // bind parameters for custom types must not be type checked against the column
+            let bind_suffix = if matches!(field.field_type, FieldType::Enum(_)) && DATABASE == "postgres" { " as _" } else { "" };
+            variables.insert(String::from("bind_suffix"), String::from(bind_suffix));
+            variables
+        })
+        .collect()
+}
+
+/// Builds the Liquid variables for the enums of an entity's enum fields.
+fn enum_variables(fields: &[Field], entity_struct_name: &str, entity_singular_name: &str) -> Vec<liquid::Object> {
+    fields
+        .iter()
+        .filter_map(|field| match &field.field_type {
+            FieldType::Enum(variants) => Some(liquid::object!({
+                "name": field.enum_name(entity_struct_name),
+                "sql_name": format!("{entity_singular_name}_{}", field.name),
+                "variants": variants.iter().map(|variant| to_pascal_case(variant)).collect::<Vec<_>>(),
+                "sql_variants": variants.iter().map(|variant| quote_sql(variant)).collect::<Vec<_>>().join(", "),
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Builds the Liquid variables for the references of an entity to other entities.
+fn reference_variables(fields: &[Field]) -> Vec<liquid::Object> {
+    fields
+        .iter()
+        .filter_map(|field| match &field.field_type {
+            FieldType::Reference(parent) => Some(liquid::object!({
+                "parent": parent,
+                "column": field.name,
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Ensures that the entities that `<name>:references` fields refer to exist so that typos surface now rather than when migrating – an entity may reference itself though, e.g. for replies to comments.
+fn ensure_referenced_entities_exist(fields: &[Field], entity_singular_name: &str) -> Result<(), anyhow::Error> {
     for field in fields {
-        let Some(captures) = re.captures(field.trim()) else {
-            return Err(anyhow!("Invalid field definition: {field}!"));
+        let FieldType::Reference(parent) = &field.field_type else {
+            continue;
         };
-        if captures.len() != 3 {
-            return Err(anyhow!("Invalid field definition: {field}!"));
-        }
-
-        let field_name = String::from(
-            captures
-                .get(1)
-                .expect("Should be able to get the field name")
-                .as_str(),
-        );
-        let field_type = captures
-            .get(2)
-            .expect("Should be able to get the field type")
-            .as_str()
-            .replace("Bool", "bool")
-            .replace("string", "String");
-
-        let mut field = HashMap::new();
-        field.insert("name".to_string(), field_name);
-        field.insert("type".to_string(), field_type);
-        mapped_fields.push(field);
-    }
-
-    Ok(mapped_fields)
+        let path = format!("./db/src/entities/{}.rs", to_plural(parent));
+        if parent != entity_singular_name && !Path::new(&path).exists() {
+            return Err(anyhow!(
+                "The entity {} that the field {} references does not exist ({path} not found)! Generate it first or fix the field's name.",
+                to_class_case(parent),
+                field.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The foreign key constraints of an entity's table.
+fn foreign_keys(fields: &[Field]) -> Vec<String> {
+    fields.iter().filter_map(Field::foreign_key).collect()
+}
+
+/// The `use` statements the entity's field types require.
+fn field_imports(fields: &[Field]) -> Vec<String> {
+    let mut imports = Vec::new();
+    if fields.iter().any(|field| matches!(field.field_type, FieldType::DateTime)) {
+        imports.push(String::from("use chrono::{DateTime, Utc};"));
+    }
+    {%- unless database == "sqlite" %}
+    if fields.iter().any(|field| matches!(field.field_type, FieldType::Decimal)) {
+        imports.push(String::from("use rust_decimal::Decimal;"));
+    }
+    {%- endunless %}
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_names_are_not_singularized() {
+        let fields = parse_fields(&[String::from("status:enum=draft,in_progress,news")]).unwrap();
+
+        assert_eq!(fields[0].enum_name("Label"), "LabelStatus");
+        let variables = enum_variables(&fields, "Label", "label");
+        assert_eq!(variables[0]["variants"], liquid::model::value!(["Draft", "InProgress", "News"]));
+    }
 }
 {% endif -%}
@@ -0,0 +1,54 @@
+use anyhow::Context;
+use clap::Parser;
+use {{crate_name}}_cli::util::ui::UI;
+use {{crate_name}}_config::{load_config, parse_env, Config, Environment};
+use {{crate_name}}_db::{connect_pool, seeds::seed};
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+
+    let args = Cli::parse();
+    let mut ui = UI::new(&mut stdout, &mut stderr, !args.no_color, !args.quiet);
+
+    match cli(&mut ui, args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            ui.error(e.to_string().as_str(), &e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(author, version, about = "A CLI tool to seed the database with fake records – use `cargo db seed --count <COUNT>` to run it.", long_about = None)]
+struct Cli {
+    #[arg(short, long, help = "Number of records to generate per entity.")]
+    count: usize,
+
+    #[arg(short, long, help = "Choose the environment (development, test, production).", value_parser = parse_env, default_value = "development")]
+    env: Environment,
+
+    #[arg(long, help = "Disable colored output.")]
+    no_color: bool,
+
+    #[arg(long, help = "Disable debug output.")]
+    quiet: bool,
+}
+
+#[allow(missing_docs)]
+async fn cli(ui: &mut UI<'_>, cli: Cli) -> Result<(), anyhow::Error> {
+    let config: Config = load_config(&cli.env).context("Could not load config!")?;
+    let db_pool = connect_pool(config.database)
+        .await
+        .context("Could not connect to the database!")?;
+
+    seed(cli.count, &db_pool)
+        .await
+        .context("Could not generate seeds!")?;
+    ui.log(&format!("Generated {} fake record(s) per entity.", cli.count));
+
+    Ok(())
+}
@@ -1,6 +1,18 @@
+{% if database == "sqlite" -%}
+CREATE TABLE IF NOT EXISTS tasks (
+    id BLOB PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL
+);
+{%- elsif database == "mysql" -%}
+CREATE TABLE IF NOT EXISTS tasks (
+    id BINARY(16) PRIMARY KEY,
+    description VARCHAR(255) NOT NULL
+);
+{%- else -%}
 CREATE TABLE IF NOT EXISTS tasks (
     id uuid PRIMARY KEY default gen_random_uuid(),
     description varchar(255) NOT NULL
 );
 
 CREATE UNIQUE INDEX IF NOT EXISTS tasks_id_idx ON tasks (id);
+{%- endif %}
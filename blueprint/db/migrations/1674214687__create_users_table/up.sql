@@ -1,3 +1,16 @@
+{% if database == "sqlite" -%}
+CREATE TABLE IF NOT EXISTS users (
+    id BLOB PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    token TEXT NOT NULL
+);
+{%- elsif database == "mysql" -%}
+CREATE TABLE IF NOT EXISTS users (
+    id BINARY(16) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    token VARCHAR(100) NOT NULL
+);
+{%- else -%}
 CREATE TABLE IF NOT EXISTS users (
     id uuid PRIMARY KEY default gen_random_uuid(),
     name varchar(255) NOT NULL,
@@ -5,3 +18,4 @@ CREATE TABLE IF NOT EXISTS users (
 );
 
 CREATE UNIQUE INDEX IF NOT EXISTS users_id_idx ON users (id);
+{%- endif %}
@@ -1,8 +1,15 @@
+{%- if database == "sqlite" -%}
+{%- assign database_type = "Sqlite" -%}
+{%- elsif database == "mysql" -%}
+{%- assign database_type = "MySql" -%}
+{%- else -%}
+{%- assign database_type = "Postgres" -%}
+{%- endif -%}
 #[cfg(feature = "test-helpers")]
 use fake::{faker::lorem::en::Sentence, Dummy};
 use serde::Deserialize;
 use serde::Serialize;
-use sqlx::Postgres;
+use sqlx::{{database_type}};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -35,11 +42,17 @@ pub struct TaskChangeset {
 
 /// Load all [`Task`]s from the database.
 pub async fn load_all(
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<Vec<Task>, crate::Error> {
+    {% if database == "postgres" -%}
     let tasks = sqlx::query_as!(Task, "SELECT id, description FROM tasks")
         .fetch_all(executor)
         .await?;
+    {%- else -%}
+    let tasks = sqlx::query_as!(Task, r#"SELECT id AS "id!: Uuid", description FROM tasks"#)
+        .fetch_all(executor)
+        .await?;
+    {%- endif %}
     Ok(tasks)
 }
 
@@ -48,9 +61,17 @@ pub async fn load_all(
 /// If no record can be found for the ID, a [`crate::Error::NoRecordFound`] will be returned.
 pub async fn load(
     id: Uuid,
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<Task, crate::Error> {
+    {% if database == "postgres" -%}
     sqlx::query_as!(Task, "SELECT id, description FROM tasks WHERE id = $1", id)
+    {%- else -%}
+    sqlx::query_as!(
+        Task,
+        r#"SELECT id AS "id!: Uuid", description FROM tasks WHERE id = ?"#,
+        id
+    )
+    {%- endif %}
         .fetch_optional(executor)
         .await
         .map_err(crate::Error::DbError)?
@@ -62,14 +83,24 @@ pub async fn load(
 /// If no record can be found for the ID, a [`crate::Error::NoRecordFound`] will be returned.
 pub async fn delete(
     id: Uuid,
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<(), crate::Error> {
+    {% if database == "postgres" -%}
     sqlx::query!("DELETE FROM tasks WHERE id = $1 RETURNING id", id)
         .fetch_optional(executor)
         .await
         .map_err(crate::Error::DbError)?
         .ok_or(crate::Error::NoRecordFound)?;
-    
+    {%- else -%}
+    let result = sqlx::query!("DELETE FROM tasks WHERE id = ?", id)
+        .execute(executor)
+        .await
+        .map_err(crate::Error::DbError)?;
+    if result.rows_affected() == 0 {
+        return Err(crate::Error::NoRecordFound);
+    }
+    {%- endif %}
+
     Ok(())
 }
 
@@ -78,10 +109,11 @@ pub async fn delete(
 /// If the data in the changeset isn't valid, a [`crate::Error::ValidationError`] will be returned, otherwise the created task is returned.
 pub async fn create(
     task: TaskChangeset,
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<Task, crate::Error> {
     task.validate()?;
 
+    {% if database == "postgres" -%}
     let record = sqlx::query!(
         "INSERT INTO tasks (description) VALUES ($1) RETURNING id",
         task.description
@@ -94,6 +126,22 @@ pub async fn create(
         id: record.id,
         description: task.description,
     })
+    {%- else -%}
+    let id = Uuid::new_v4();
+    sqlx::query!(
+        "INSERT INTO tasks (id, description) VALUES (?, ?)",
+        id,
+        task.description
+    )
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+
+    Ok(Task {
+        id,
+        description: task.description,
+    })
+    {%- endif %}
 }
 
 /// Updates a task in the database with the data in the passed [`TaskChangeset`].
@@ -102,10 +150,11 @@ pub async fn create(
 pub async fn update(
     id: Uuid,
     task: TaskChangeset,
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<Task, crate::Error> {
     task.validate()?;
 
+    {% if database == "postgres" -%}
     sqlx::query!(
         "UPDATE tasks SET description = $1 WHERE id = $2 RETURNING id, description",
         task.description,
@@ -119,4 +168,22 @@ pub async fn update(
         id: record.id,
         description: record.description,
     })
+    {%- else -%}
+    let result = sqlx::query!(
+        "UPDATE tasks SET description = ? WHERE id = ?",
+        task.description,
+        id
+    )
+    .execute(executor)
+    .await
+    .map_err(crate::Error::DbError)?;
+    if result.rows_affected() == 0 {
+        return Err(crate::Error::NoRecordFound);
+    }
+
+    Ok(Task {
+        id,
+        description: task.description,
+    })
+    {%- endif %}
 }
@@ -1,5 +1,12 @@
+{%- if database == "sqlite" -%}
+{%- assign database_type = "Sqlite" -%}
+{%- elsif database == "mysql" -%}
+{%- assign database_type = "MySql" -%}
+{%- else -%}
+{%- assign database_type = "Postgres" -%}
+{%- endif -%}
 use serde::Serialize;
-use sqlx::Postgres;
+use sqlx::{{database_type}};
 use uuid::Uuid;
 
 /// A user record.
@@ -16,10 +23,18 @@ pub struct User {
 /// If no user exists for the token, [`Option::None`] is returned, otherwise `Option::Some(User)` is returned.
 pub async fn load_with_token(
     token: &str,
-    executor: impl sqlx::Executor<'_, Database = Postgres>,
+    executor: impl sqlx::Executor<'_, Database = {{database_type}}>,
 ) -> Result<Option<User>, anyhow::Error> {
     Ok(
+        {% if database == "postgres" -%}
         sqlx::query_as!(User, "SELECT id, name FROM users WHERE token = $1", token)
+        {%- else -%}
+        sqlx::query_as!(
+            User,
+            r#"SELECT id AS "id!: Uuid", name FROM users WHERE token = ?"#,
+            token
+        )
+        {%- endif %}
             .fetch_optional(executor)
             .await?,
     )
@@ -2,10 +2,22 @@
 
 use anyhow::{Context, Result};
 use {{crate_name}}_config::DatabaseConfig;
+{% if database == "sqlite" -%}
+use sqlx::{sqlite::SqlitePoolOptions, Sqlite, Transaction};
+{%- elsif database == "mysql" -%}
+use sqlx::{mysql::MySqlPoolOptions, MySql, Transaction};
+{%- else -%}
 use sqlx::{postgres::PgPoolOptions, Postgres, Transaction};
+{%- endif %}
 use thiserror::Error;
 
+{% if database == "sqlite" -%}
+pub use sqlx::sqlite::SqlitePool as DbPool;
+{%- elsif database == "mysql" -%}
+pub use sqlx::mysql::MySqlPool as DbPool;
+{%- else -%}
 pub use sqlx::postgres::PgPool as DbPool;
+{%- endif %}
 
 /// Entity definitions and related functions
 pub mod entities;
@@ -27,7 +39,7 @@ pub mod entities;
 /// Transactions are rolled back automatically when they are dropped without having been committed.
 pub async fn transaction(
     db_pool: &DbPool,
-) -> Result<Transaction<'static, Postgres>, anyhow::Error> {
+) -> Result<Transaction<'static, {% if database == "sqlite" %}Sqlite{% elsif database == "mysql" %}MySql{% else %}Postgres{% endif %}>, anyhow::Error> {
     let tx = db_pool
         .begin()
         .await
@@ -55,7 +67,7 @@ pub enum Error {
 
 /// Creates a connection pool to the database specified in the passed [`{{project-name}}-config::DatabaseConfig`]
 pub async fn connect_pool(config: DatabaseConfig) -> Result<DbPool, anyhow::Error> {
-    let pool = PgPoolOptions::new()
+    let pool = {% if database == "sqlite" %}SqlitePoolOptions{% elsif database == "mysql" %}MySqlPoolOptions{% else %}PgPoolOptions{% endif %}::new()
         .connect(config.url.as_str())
         .await
         .context("Failed to connect to database")?;
@@ -66,3 +78,7 @@ pub async fn connect_pool(config: DatabaseConfig) -> Result<DbPool, anyhow::Erro
 /// Functionality for working with data that is only relevant in tests but not as part of the normal application flow.
 #[cfg(feature = "test-helpers")]
 pub mod test_helpers;
+
+/// Generating fake records for seeding the database via `cargo db seed --count <COUNT>`.
+#[cfg(feature = "test-helpers")]
+pub mod seeds;
@@ -0,0 +1,36 @@
+{% if with_examples -%}
+use crate::entities::tasks::{self, TaskChangeset};
+{% endif -%}
+{% if with_auth -%}
+use crate::test_helpers::users::{self, UserChangeset};
+{% endif -%}
+use crate::DbPool;
+{% if with_examples or with_auth -%}
+use fake::{Fake, Faker};
+{% endif %}
+/// Generates `count` fake records of every entity, e.g. to fill the development database with realistic amounts of data.
+///
+/// The records are built from the changesets' [`fake::Dummy`] implementations and stored via the entities' regular `create` functions. This function is invoked by `cargo db seed --count <COUNT>` – add new entities here to have them seeded as well, e.g.:
+///
+/// ```
+/// for _ in 0..count {
+///     let changeset: ProjectChangeset = Faker.fake();
+///     projects::create(changeset, db_pool).await?;
+/// }
+/// ```
+pub async fn seed({% unless with_examples or with_auth %}_{% endunless %}count: usize, {% unless with_examples or with_auth %}_{% endunless %}db_pool: &DbPool) -> Result<(), anyhow::Error> {
+    {%- if with_auth %}
+    for _ in 0..count {
+        let user: UserChangeset = Faker.fake();
+        users::create(user, db_pool).await?;
+    }
+    {%- endif %}
+    {%- if with_examples %}
+    for _ in 0..count {
+        let task: TaskChangeset = Faker.fake();
+        tasks::create(task, db_pool).await?;
+    }
+    {%- endif %}
+
+    Ok(())
+}
@@ -1,6 +1,14 @@
 use crate::entities::users::User;
 use fake::{faker::name::en::Name, Dummy};
+{% if database == "sqlite" -%}
+use sqlx::sqlite::SqlitePool;
+use uuid::Uuid;
+{%- elsif database == "mysql" -%}
+use sqlx::mysql::MySqlPool;
+use uuid::Uuid;
+{%- else -%}
 use sqlx::postgres::PgPool;
+{%- endif %}
 
 /// A changeset representing the data that is intended to be used to either create a new user or update an existing user.
 ///
@@ -24,6 +32,7 @@ pub struct UserChangeset {
 /// Creates a user in the database with the data in the passed [`UserChangeset`].
 ///
 /// If the data in the changeset isn't valid, a [`crate::Error::ValidationError`] will be returned, otherwise the created user is returned.
+{% if database == "postgres" -%}
 pub async fn create(user: UserChangeset, db: &PgPool) -> Result<User, anyhow::Error> {
     let record = sqlx::query!(
         "INSERT INTO users (name, token) VALUES ($1, $2) RETURNING id",
@@ -38,3 +47,18 @@ pub async fn create(user: UserChangeset, db: &PgPool) -> Result<User, anyhow::Er
         name: user.name,
     })
 }
+{%- else -%}
+pub async fn create(user: UserChangeset, db: &{% if database == "sqlite" %}SqlitePool{% else %}MySqlPool{% endif %}) -> Result<User, anyhow::Error> {
+    let id = Uuid::new_v4();
+    sqlx::query!(
+        "INSERT INTO users (id, name, token) VALUES (?, ?, ?)",
+        id,
+        user.name,
+        user.token,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(User { id, name: user.name })
+}
+{%- endif %}
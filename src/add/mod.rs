@@ -0,0 +1,82 @@
+use crate::features::{Database, Feature, Features};
+use crate::ui::UI;
+use crate::update::{UpdateReport, merge_into_project, read_project_settings};
+use crate::{VERSION, build_template_path, format_project, render};
+use anyhow::{Context, bail};
+use std::path::Path;
+
+/// Adds features to the project in `project_dir` after it has been generated.
+///
+/// The blueprint is rendered twice – once with the features the project was generated with (the base) and once with the added features (and all features they imply) on top. The difference between the two, e.g. the `db` crate or the `db_pool` in the web crate's `AppState`, is then merged into the project's files the same way `gerust update` does so that local changes are preserved.
+pub fn add(
+    ui: &mut UI,
+    project_dir: &Path,
+    with: &[Feature],
+    database: Option<Database>,
+) -> Result<UpdateReport, anyhow::Error> {
+    let settings = read_project_settings(project_dir)?;
+    let database = match database {
+        Some(database)
+            if database != settings.database && settings.features.contains(Feature::Db) =>
+        {
+            bail!(
+                "The project uses {} already – switching the database backend is not supported!",
+                settings.database
+            );
+        }
+        Some(database) => database,
+        None => settings.database,
+    };
+    if settings.generated_with != VERSION {
+        bail!(
+            "The project was generated with Gerust {} – run gerust update to update it to Gerust {VERSION} first!",
+            settings.generated_with
+        );
+    }
+
+    let mut features = settings.features.clone();
+    features.include(with);
+    if features == settings.features {
+        ui.log(&format!(
+            "Project includes {} already.",
+            Features::from_iter(with.iter().copied())
+        ));
+        return Ok(UpdateReport::default());
+    }
+    ui.log(&format!("Features: {features}"));
+    if features.contains(Feature::Db) {
+        ui.log(&format!("Database: {database}"));
+    }
+
+    let template_path = build_template_path()?;
+
+    ui.log("Rendering blueprint with the project's current features…");
+    let base_dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let base_project_dir = render(
+        &settings.project_name,
+        base_dir.path(),
+        false,
+        &settings.features,
+        settings.database,
+        VERSION,
+        template_path.clone(),
+    )
+    .context("Failed to render the blueprint with the project's current features")?;
+    format_project(&base_project_dir);
+
+    ui.log("Rendering blueprint with the added features…");
+    let new_dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let new_project_dir = render(
+        &settings.project_name,
+        new_dir.path(),
+        false,
+        &features,
+        database,
+        VERSION,
+        template_path,
+    )
+    .context("Failed to render the blueprint with the added features")?;
+    format_project(&new_project_dir);
+
+    merge_into_project(ui, &base_project_dir, &new_project_dir, project_dir)
+}
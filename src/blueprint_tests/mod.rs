@@ -0,0 +1,140 @@
+//! Renders every variant of the blueprint and snapshots the generated files so that changes to the blueprint's Liquid templates and `cargo-generate.toml` can be reviewed in the resulting projects.
+//!
+//! The blueprint is rendered from the copy that is compiled into Gerust, exactly like `gerust` does, without formatting the result so that the snapshots do not depend on the installed version of rustfmt. Update the snapshots after changing the blueprint with `INSTA_UPDATE=always cargo test` or `cargo insta review`.
+
+use crate::features::{Database, Feature, Features};
+use crate::{BLUEPRINTS_DIR, Blueprint, render};
+use cargo_generate::TemplatePath;
+use insta::assert_snapshot;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// The Gerust version rendered into the snapshots instead of the actual one so they don't change with every commit.
+const VERSION: &str = "0.0.0 (snapshot)";
+
+fn render_snapshot(features: &Features, database: Database) -> String {
+    let blueprint_dir = tempfile::tempdir().unwrap();
+    BLUEPRINTS_DIR.extract(blueprint_dir.path()).unwrap();
+    let template_path = TemplatePath {
+        path: Some(blueprint_dir.path().display().to_string()),
+        ..Default::default()
+    };
+
+    let output_dir = tempfile::tempdir().unwrap();
+    let project_dir = render(
+        "my-app",
+        output_dir.path(),
+        false,
+        features,
+        database,
+        VERSION,
+        template_path,
+    )
+    .unwrap();
+
+    let mut files: Vec<_> = WalkDir::new(&project_dir)
+        .into_iter()
+        .map(Result::unwrap)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            entry
+                .path()
+                .strip_prefix(&project_dir)
+                .unwrap()
+                .to_path_buf()
+        })
+        .collect();
+    files.sort();
+
+    let mut snapshot = String::new();
+    for file in &files {
+        snapshot.push_str(&format!("{}\n", file.display()));
+    }
+    for file in &files {
+        let contents = fs::read_to_string(project_dir.join(file)).unwrap();
+        snapshot.push_str(&format!("\n===== {} =====\n{contents}", file.display()));
+    }
+    snapshot
+}
+
+#[test]
+fn test_minimal() {
+    let snapshot = render_snapshot(&Features::from(Blueprint::Minimal), Database::Postgres);
+    assert_snapshot!("minimal", snapshot);
+}
+
+#[test]
+fn test_default() {
+    let snapshot = render_snapshot(&Features::from(Blueprint::Default), Database::Postgres);
+    assert_snapshot!("default", snapshot);
+}
+
+#[test]
+fn test_full() {
+    let snapshot = render_snapshot(&Features::from(Blueprint::Full), Database::Postgres);
+    assert_snapshot!("full", snapshot);
+}
+
+#[test]
+fn test_full_mysql() {
+    let snapshot = render_snapshot(&Features::from(Blueprint::Full), Database::Mysql);
+    assert_snapshot!("full_mysql", snapshot);
+}
+
+#[test]
+fn test_full_sqlite() {
+    let snapshot = render_snapshot(&Features::from(Blueprint::Full), Database::Sqlite);
+    assert_snapshot!("full_sqlite", snapshot);
+}
+
+#[test]
+fn test_default_docker() {
+    let features = Features::build(Blueprint::Default, &[Feature::Docker], &[]);
+    let snapshot = render_snapshot(&features, Database::Postgres);
+    assert_snapshot!("default_docker", snapshot);
+}
+
+#[test]
+fn test_ignored_paths_exist() {
+    let config = BLUEPRINTS_DIR
+        .get_file("cargo-generate.toml")
+        .and_then(|file| file.contents_utf8())
+        .unwrap()
+        .parse::<toml::Table>()
+        .unwrap();
+
+    let as_paths = |value: Option<&toml::Value>| -> Vec<String> {
+        value
+            .and_then(toml::Value::as_array)
+            .map(|paths| {
+                paths
+                    .iter()
+                    .map(|path| String::from(path.as_str().unwrap()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    // the template's own ignore list only refers to build artifacts that never exist in the blueprint
+    let mut paths = as_paths(config["template"].get("exclude"));
+    for conditional in config["conditional"].as_table().unwrap().values() {
+        paths.extend(as_paths(conditional.get("ignore")));
+    }
+    assert!(!paths.is_empty());
+
+    // cargo-generate removes ignored files after rendering, so entries refer to `*.liquid` files by their rendered names
+    let missing: Vec<&String> = paths
+        .iter()
+        .filter(|path| {
+            BLUEPRINTS_DIR.get_entry(Path::new(path)).is_none()
+                && BLUEPRINTS_DIR
+                    .get_entry(Path::new(&format!("{path}.liquid")))
+                    .is_none()
+        })
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "cargo-generate.toml references paths that do not exist in the blueprint: {missing:?}"
+    );
+}
@@ -0,0 +1,454 @@
+use crate::features::{Feature, Features};
+use crate::ui::UI;
+use crate::update::read_project_settings;
+use anyhow::{Context, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The crates every project consists of, identified by the path of the workspace member; the `generate` and `db` commands look crates up by these paths.
+const CRATES: [&str; 4] = ["cli", "config", "macros", "web"];
+
+/// The environments the `config` crate loads settings for.
+const ENVIRONMENTS: [&str; 3] = ["development", "production", "test"];
+
+/// How serious a problem found by `gerust doctor` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Something that should be fixed but does not break the project, e.g. missing `.sqlx` offline data.
+    Warning,
+    /// Something that breaks the project or Gerust's tooling.
+    Error,
+}
+
+/// A problem found by `gerust doctor` along with how to fix it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+    pub fix: String,
+}
+
+impl Problem {
+    fn error(message: impl Into<String>, fix: impl Into<String>) -> Problem {
+        Problem {
+            severity: Severity::Error,
+            message: message.into(),
+            fix: fix.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Problem {
+        Problem {
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: fix.into(),
+        }
+    }
+}
+
+/// The result of one of the checks `gerust doctor` runs.
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub problems: Vec<Problem>,
+}
+
+impl Check {
+    pub fn errors(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.problems.len() - self.errors()
+    }
+}
+
+/// Checks the project in `project_dir` (see [`diagnose`]) and prints the problems found along with how to fix them.
+pub fn doctor(ui: &mut UI, project_dir: &Path) -> Result<Vec<Check>, anyhow::Error> {
+    let checks = diagnose(project_dir)?;
+    for check in &checks {
+        if check.problems.is_empty() {
+            ui.success(check.name);
+        }
+        for problem in &check.problems {
+            let message = format!("{}: {}", check.name, problem.message);
+            match problem.severity {
+                Severity::Warning => ui.warning(&message),
+                Severity::Error => ui.error(&message, &anyhow!("{}", problem.message)),
+            }
+            ui.indent();
+            ui.log(&problem.fix);
+            ui.outdent();
+        }
+    }
+    Ok(checks)
+}
+
+/// Checks whether the project in `project_dir` still matches the structure Gerust and the project's own tooling expect.
+///
+/// The checks cover the workspace's crates (inspected via `cargo metadata`), the configuration files, the `.env` files, as well as – for projects with database access – the migrations and the `.sqlx` offline query data.
+pub fn diagnose(project_dir: &Path) -> Result<Vec<Check>, anyhow::Error> {
+    let settings = read_project_settings(project_dir)?;
+    let features = &settings.features;
+
+    let mut checks = vec![
+        Check {
+            name: "Workspace crates",
+            problems: match workspace_members(project_dir) {
+                Ok(members) => check_crates(&members, features),
+                Err(e) => vec![Problem::error(
+                    format!("cargo metadata failed: {e:#}"),
+                    "Make sure the workspace's Cargo.toml is valid and all members listed in it exist.",
+                )],
+            },
+        },
+        Check {
+            name: "Configuration",
+            problems: check_config(project_dir),
+        },
+        Check {
+            name: "Environment files",
+            problems: check_env_files(project_dir, features),
+        },
+    ];
+    if features.contains(Feature::Db) {
+        checks.push(Check {
+            name: "Migrations",
+            problems: check_migrations(project_dir),
+        });
+        checks.push(Check {
+            name: "SQLx offline data",
+            problems: check_sqlx_offline_data(project_dir),
+        });
+    }
+
+    Ok(checks)
+}
+
+/// Lists the paths of the workspace's members relative to the workspace root via `cargo metadata`.
+fn workspace_members(project_dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let output = std::process::Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(project_dir)
+        .output()
+        .context("Failed to run cargo metadata")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().next().unwrap_or_default();
+        return Err(anyhow!(
+            "{}",
+            message.strip_prefix("error: ").unwrap_or(message)
+        ));
+    }
+
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata output")?;
+    let workspace_root = metadata["workspace_root"]
+        .as_str()
+        .map(PathBuf::from)
+        .context("cargo metadata did not report the workspace root")?;
+    let member_ids: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .map(|ids| ids.iter().filter_map(serde_json::Value::as_str).collect())
+        .unwrap_or_default();
+
+    let members = metadata["packages"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|package| {
+            package["id"]
+                .as_str()
+                .is_some_and(|id| member_ids.contains(&id))
+        })
+        .filter_map(|package| package["manifest_path"].as_str())
+        .filter_map(|manifest_path| {
+            Path::new(manifest_path)
+                .parent()?
+                .strip_prefix(&workspace_root)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+
+    Ok(members)
+}
+
+fn check_crates(members: &[PathBuf], features: &Features) -> Vec<Problem> {
+    let mut expected = Vec::from(CRATES);
+    if features.contains(Feature::Db) {
+        expected.push("db");
+    }
+
+    expected
+        .into_iter()
+        .filter(|path| !members.iter().any(|member| member == Path::new(path)))
+        .map(|path| {
+            Problem::error(
+                format!(r#"There is no workspace member at "{path}"."#),
+                format!(
+                    r#"The generate and db commands look up crates by their path – move the {path} crate back to {path}/ and make sure "{path}" is listed in the members of the workspace's Cargo.toml."#
+                ),
+            )
+        })
+        .collect()
+}
+
+fn check_config(project_dir: &Path) -> Vec<Problem> {
+    let mut files = vec![PathBuf::from("config/app.toml")];
+    files.extend(
+        ENVIRONMENTS
+            .iter()
+            .map(|environment| PathBuf::from(format!("config/environments/{environment}.toml"))),
+    );
+
+    files
+        .into_iter()
+        .filter(|file| !project_dir.join(file).is_file())
+        .map(|file| {
+            Problem::error(
+                format!("{} is missing.", file.display()),
+                format!(
+                    "The config crate fails to load the configuration without it – restore {} from version control or a newly generated project.",
+                    file.display()
+                ),
+            )
+        })
+        .collect()
+}
+
+fn check_env_files(project_dir: &Path, features: &Features) -> Vec<Problem> {
+    let mut problems = vec![];
+    for file in [".env", ".env.test"] {
+        let path = project_dir.join(file);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            problems.push(Problem::error(
+                format!("{file} is missing."),
+                format!(
+                    "Create {file} – the project's binaries and tests read environment variables from it."
+                ),
+            ));
+            continue;
+        };
+        if features.contains(Feature::Db) {
+            problems.extend(
+                ["DATABASE_URL", "APP_DATABASE__URL"]
+                    .into_iter()
+                    .filter(|variable| !defines_variable(&contents, variable))
+                    .map(|variable| {
+                        Problem::error(
+                            format!("{file} does not define {variable}."),
+                            format!(
+                                "Add {variable} to {file} – see the README for the expected value."
+                            ),
+                        )
+                    }),
+            );
+        }
+    }
+    problems
+}
+
+fn defines_variable(contents: &str, variable: &str) -> bool {
+    contents.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix(variable)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+fn check_migrations(project_dir: &Path) -> Vec<Problem> {
+    let migrations_dir = project_dir.join("db/migrations");
+    let Ok(entries) = fs::read_dir(&migrations_dir) else {
+        return vec![Problem::error(
+            "db/migrations is missing.",
+            "Create the db/migrations directory – cargo db migrate runs the migrations in it.",
+        )];
+    };
+
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut problems = vec![];
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if !is_migration_name(&name) {
+                problems.push(invalid_migration_name(&name));
+                continue;
+            }
+            for file in ["up.sql", "down.sql"] {
+                if !path.join(file).is_file() {
+                    problems.push(Problem::error(
+                        format!("db/migrations/{name} does not contain {file}."),
+                        format!(
+                            "Add {file} to the migration or turn it into a simple migration db/migrations/{name}.sql."
+                        ),
+                    ));
+                }
+            }
+        } else {
+            match name.strip_suffix(".sql") {
+                Some(stem) if is_migration_name(stem) => {}
+                _ => problems.push(invalid_migration_name(&name)),
+            }
+        }
+    }
+    problems
+}
+
+fn is_migration_name(name: &str) -> bool {
+    name.split_once("__")
+        .or_else(|| name.split_once('_'))
+        .is_some_and(|(version, description)| {
+            !version.is_empty()
+                && version.chars().all(|c| c.is_ascii_digit())
+                && !description.is_empty()
+        })
+}
+
+fn invalid_migration_name(name: &str) -> Problem {
+    Problem::error(
+        format!("db/migrations/{name} is not a valid migration."),
+        "Migrations must be named <version>__<description>, either as a directory with up.sql and down.sql or as a single .sql file – use cargo generate migration to create them.",
+    )
+}
+
+fn check_sqlx_offline_data(project_dir: &Path) -> Vec<Problem> {
+    let has_query_data = fs::read_dir(project_dir.join("db/.sqlx"))
+        .map(|entries| {
+            entries.filter_map(Result::ok).any(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+        })
+        .unwrap_or(false);
+
+    if has_query_data {
+        vec![]
+    } else {
+        vec![Problem::warning(
+            "db/.sqlx does not contain any query data.",
+            "Builds with SQLX_OFFLINE=1, e.g. on CI, will fail – run cargo db prepare with the database running and check db/.sqlx into version control.",
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Blueprint;
+
+    #[test]
+    fn test_check_crates() {
+        let members = vec![
+            PathBuf::from("cli"),
+            PathBuf::from("config"),
+            PathBuf::from("crates/db"),
+            PathBuf::from("web"),
+        ];
+
+        let problems = check_crates(&members, &Features::from(Blueprint::Minimal));
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].message,
+            r#"There is no workspace member at "macros"."#
+        );
+
+        let problems = check_crates(&members, &Features::from(Blueprint::Default));
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| problem.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                r#"There is no workspace member at "macros"."#,
+                r#"There is no workspace member at "db"."#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_env_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".env"),
+            "DATABASE_URL=\"postgresql://localhost/app\"\nAPP_DATABASE__URL=\"${DATABASE_URL}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".env.test"),
+            "# APP_DATABASE__URL is not set\nDATABASE_URL = \"postgresql://localhost/app_test\"\n",
+        )
+        .unwrap();
+
+        assert!(check_env_files(dir.path(), &Features::from(Blueprint::Minimal)).is_empty());
+
+        let problems = check_env_files(dir.path(), &Features::from(Blueprint::Default));
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].message,
+            ".env.test does not define APP_DATABASE__URL."
+        );
+    }
+
+    #[test]
+    fn test_check_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let migrations_dir = dir.path().join("db/migrations");
+        fs::create_dir_all(migrations_dir.join("1674214685__create_tasks_table")).unwrap();
+        fs::write(
+            migrations_dir.join("1674214685__create_tasks_table/up.sql"),
+            "",
+        )
+        .unwrap();
+        fs::write(
+            migrations_dir.join("1674214685__create_tasks_table/down.sql"),
+            "",
+        )
+        .unwrap();
+        fs::create_dir_all(migrations_dir.join("1674214687__create_users_table")).unwrap();
+        fs::write(
+            migrations_dir.join("1674214687__create_users_table/up.sql"),
+            "",
+        )
+        .unwrap();
+        fs::write(migrations_dir.join("1674214690__add_index.sql"), "").unwrap();
+        fs::write(migrations_dir.join("add_column.sql"), "").unwrap();
+        fs::write(migrations_dir.join(".gitkeep"), "").unwrap();
+
+        let problems: Vec<String> = check_migrations(dir.path())
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "db/migrations/1674214687__create_users_table does not contain down.sql.",
+                "db/migrations/add_column.sql is not a valid migration.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_sqlx_offline_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let problems = check_sqlx_offline_data(dir.path());
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Warning);
+
+        fs::create_dir_all(dir.path().join("db/.sqlx")).unwrap();
+        fs::write(dir.path().join("db/.sqlx/query-0a1b2c.json"), "{}").unwrap();
+        assert!(check_sqlx_offline_data(dir.path()).is_empty());
+    }
+}
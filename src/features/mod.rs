@@ -0,0 +1,272 @@
+use crate::Blueprint;
+use std::collections::BTreeSet;
+
+/// A capability that can be included in a generated project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Feature {
+    /// Database access via the `db` crate, the `cargo db` CLI, and a Docker setup for PostgreSQL
+    Db,
+    /// Token-based authentication via the `auth` middleware and a `users` entity (implies `db`)
+    Auth,
+    /// Example implementations of a controller, entity, migration, and tests
+    Examples,
+    /// Seed data for the example user (implies `auth`)
+    Seeds,
+    /// A Dockerfile and a Docker Compose service for building and running the application in a container
+    Docker,
+}
+
+impl Feature {
+    /// All features, in the order they are listed in the project's metadata – features always come after the features they imply.
+    pub const ALL: [Feature; 5] = [
+        Feature::Db,
+        Feature::Auth,
+        Feature::Examples,
+        Feature::Seeds,
+        Feature::Docker,
+    ];
+
+    /// The name of the feature as used on the command line and in the project's metadata.
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Db => "db",
+            Feature::Auth => "auth",
+            Feature::Examples => "examples",
+            Feature::Seeds => "seeds",
+            Feature::Docker => "docker",
+        }
+    }
+
+    /// The placeholder that enables the feature in the blueprint's Liquid templates and `cargo-generate.toml`.
+    pub fn placeholder(self) -> String {
+        format!("with_{}", self.name())
+    }
+
+    fn implied(self) -> Option<Feature> {
+        match self {
+            Feature::Auth => Some(Feature::Db),
+            Feature::Seeds => Some(Feature::Auth),
+            Feature::Db | Feature::Examples | Feature::Docker => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The database backend a project with the [`Feature::Db`] feature uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Database {
+    /// PostgreSQL, run via Docker in development and test
+    #[default]
+    Postgres,
+    /// MySQL, run via Docker in development and test
+    Mysql,
+    /// SQLite, storing the databases in files so that no Docker setup is needed
+    Sqlite,
+}
+
+impl Database {
+    /// The name of the database as used on the command line, in the project's metadata, and for the blueprint's `database` placeholder.
+    pub fn name(self) -> &'static str {
+        match self {
+            Database::Postgres => "postgres",
+            Database::Mysql => "mysql",
+            Database::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl std::str::FromStr for Database {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(Database::Postgres),
+            "mysql" => Ok(Database::Mysql),
+            "sqlite" => Ok(Database::Sqlite),
+            unknown => Err(anyhow::anyhow!(r#"Unknown database: "{unknown}"!"#)),
+        }
+    }
+}
+
+impl std::fmt::Display for Database {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The set of [`Feature`]s a project is generated with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Features(BTreeSet<Feature>);
+
+impl Features {
+    /// Builds the features for a blueprint, adding and removing individual features on top of it.
+    ///
+    /// Features implied by other features (e.g. `db` for `auth`) are added automatically unless they are removed explicitly, in which case the implying features are removed as well.
+    pub fn build(blueprint: Blueprint, with: &[Feature], without: &[Feature]) -> Features {
+        let mut features = Features::from(blueprint);
+        features.include(with);
+
+        for feature in without {
+            features.0.remove(feature);
+        }
+        for feature in Feature::ALL {
+            if feature
+                .implied()
+                .is_some_and(|dependency| !features.contains(dependency))
+            {
+                features.0.remove(&feature);
+            }
+        }
+
+        features
+    }
+
+    /// Adds features along with all features they imply.
+    pub fn include(&mut self, with: &[Feature]) {
+        for feature in with {
+            let mut feature = Some(*feature);
+            while let Some(included) = feature {
+                self.0.insert(included);
+                feature = included.implied();
+            }
+        }
+    }
+
+    /// Whether the feature is included.
+    pub fn contains(&self, feature: Feature) -> bool {
+        self.0.contains(&feature)
+    }
+
+    /// The blueprint these features correspond to or `None` if they don't match any of the predefined blueprints.
+    pub fn blueprint(&self) -> Option<Blueprint> {
+        [Blueprint::Minimal, Blueprint::Default, Blueprint::Full]
+            .into_iter()
+            .find(|blueprint| Features::from(*blueprint) == *self)
+    }
+
+    /// The value for the blueprint's `template_type` placeholder – the name of the matching predefined blueprint or `custom`.
+    pub fn template_type(&self) -> String {
+        self.blueprint()
+            .map_or_else(|| String::from("custom"), |blueprint| blueprint.to_string())
+    }
+
+    /// The placeholder definitions enabling or disabling each feature in the blueprint.
+    pub fn defines(&self) -> Vec<String> {
+        Feature::ALL
+            .into_iter()
+            .map(|feature| format!("{}={}", feature.placeholder(), self.contains(feature)))
+            .collect()
+    }
+}
+
+impl From<Blueprint> for Features {
+    fn from(blueprint: Blueprint) -> Self {
+        let features: &[Feature] = match blueprint {
+            Blueprint::Minimal => &[Feature::Examples],
+            Blueprint::Default => &[Feature::Db],
+            Blueprint::Full => &[
+                Feature::Db,
+                Feature::Auth,
+                Feature::Examples,
+                Feature::Seeds,
+            ],
+        };
+
+        Features(features.iter().copied().collect())
+    }
+}
+
+impl FromIterator<Feature> for Features {
+    fn from_iter<T: IntoIterator<Item = Feature>>(iter: T) -> Self {
+        Features(iter.into_iter().collect())
+    }
+}
+
+impl std::fmt::Display for Features {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(|feature| feature.name()).collect();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Database, Feature, Features};
+    use crate::Blueprint;
+
+    #[test]
+    fn test_blueprints() {
+        for blueprint in [Blueprint::Minimal, Blueprint::Default, Blueprint::Full] {
+            let features = Features::build(blueprint, &[], &[]);
+            assert_eq!(features.template_type(), blueprint.to_string());
+        }
+    }
+
+    #[test]
+    fn test_with() {
+        let features = Features::build(Blueprint::Default, &[Feature::Examples], &[]);
+        assert_eq!(features.to_string(), "db, examples");
+        assert_eq!(features.template_type(), "custom");
+    }
+
+    #[test]
+    fn test_with_implied() {
+        let features = Features::build(Blueprint::Minimal, &[Feature::Seeds], &[]);
+        assert_eq!(features.to_string(), "db, auth, examples, seeds");
+        assert_eq!(features.template_type(), "full");
+    }
+
+    #[test]
+    fn test_with_docker() {
+        let features = Features::build(Blueprint::Full, &[Feature::Docker], &[]);
+        assert_eq!(features.to_string(), "db, auth, examples, seeds, docker");
+        assert_eq!(features.template_type(), "custom");
+    }
+
+    #[test]
+    fn test_without() {
+        let features = Features::build(Blueprint::Full, &[], &[Feature::Seeds]);
+        assert_eq!(features.to_string(), "db, auth, examples");
+    }
+
+    #[test]
+    fn test_without_implied() {
+        let features = Features::build(Blueprint::Full, &[], &[Feature::Db]);
+        assert_eq!(features.to_string(), "examples");
+        assert_eq!(features.template_type(), "minimal");
+    }
+
+    #[test]
+    fn test_include() {
+        let mut features = Features::build(Blueprint::Minimal, &[], &[]);
+        features.include(&[Feature::Auth]);
+        assert_eq!(features.to_string(), "db, auth, examples");
+    }
+
+    #[test]
+    fn test_database_names() {
+        for database in [Database::Postgres, Database::Mysql, Database::Sqlite] {
+            assert_eq!(database.name().parse::<Database>().unwrap(), database);
+        }
+    }
+
+    #[test]
+    fn test_defines() {
+        let features = Features::build(Blueprint::Default, &[], &[]);
+        assert_eq!(
+            features.defines(),
+            vec![
+                "with_db=true",
+                "with_auth=false",
+                "with_examples=false",
+                "with_seeds=false",
+                "with_docker=false"
+            ]
+        );
+    }
+}
@@ -3,15 +3,43 @@
 use anyhow::Context;
 use cargo_generate::Vcs;
 use cargo_generate::{GenerateArgs, TemplatePath};
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use features::{Database, Feature, Features};
+use heck::{ToKebabCase, ToSnakeCase};
+use report::{Exit, Formatting, Output, Report, VcsInit};
+use source::BlueprintSource;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tempfile::TempDir;
 
 #[allow(dead_code)]
 #[doc(hidden)]
 mod ui;
 
+#[doc(hidden)]
+mod features;
+
+#[doc(hidden)]
+mod update;
+
+#[doc(hidden)]
+mod add;
+
+#[doc(hidden)]
+mod doctor;
+
+#[doc(hidden)]
+mod report;
+
+#[doc(hidden)]
+mod source;
+
+#[cfg(test)]
+mod blueprint_tests;
+
 #[doc(hidden)]
 static VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("VERGEN_GIT_SHA"), ")");
 
@@ -27,6 +55,19 @@ enum Blueprint {
     Full,
 }
 
+impl std::str::FromStr for Blueprint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(Blueprint::Minimal),
+            "default" => Ok(Blueprint::Default),
+            "full" => Ok(Blueprint::Full),
+            unknown => Err(anyhow::anyhow!(r#"Unknown blueprint: "{unknown}"!"#)),
+        }
+    }
+}
+
 impl std::fmt::Display for Blueprint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -40,13 +81,20 @@ impl std::fmt::Display for Blueprint {
 #[doc(hidden)]
 #[derive(Parser)]
 #[command(author, version, about = "A backend project generator and manager.", long_about = None)]
-#[command(propagate_version = true, arg_required_else_help(true))]
+#[command(
+    propagate_version = true,
+    arg_required_else_help(true),
+    args_conflicts_with_subcommands = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     #[arg(
         index = 1,
         help = "The name of the project (Cargo workspace) to generate (will be used as a prefix for the individual crates)"
     )]
-    name: String,
+    name: Option<String>,
     #[arg(
         short,
         long,
@@ -68,6 +116,49 @@ struct Cli {
         help = "Generate a minimal project without database access"
     )]
     minimal: bool,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        value_name = "FEATURE",
+        help = "Include a feature in addition to the ones of the chosen blueprint; can be passed multiple times"
+    )]
+    with: Vec<Feature>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FEATURE",
+        help = "Exclude a feature that the chosen blueprint would include; can be passed multiple times"
+    )]
+    without: Vec<Feature>,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t,
+        help = "The database backend to use if the project includes database access"
+    )]
+    database: Database,
+    #[arg(
+        long,
+        value_name = "PATH|GIT-URL",
+        help = "Generate the project from a custom blueprint, e.g. a fork of Gerust's blueprint, instead of the built-in one"
+    )]
+    blueprint: Option<BlueprintSource>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        help = "How to report the outcome; json prints a single machine-readable report to stdout"
+    )]
+    output: Output,
+    #[arg(long, help = "Do not format the generated project with cargo fmt")]
+    no_fmt: bool,
+    #[arg(
+        long,
+        help = "List the files that would be generated without writing anything"
+    )]
+    dry_run: bool,
 
     #[arg(long, global = true, help = "Disable VCS initialization")]
     no_vcs: bool,
@@ -79,15 +170,185 @@ struct Cli {
     debug: bool,
 }
 
+#[doc(hidden)]
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Update an existing project to the blueprint of this version of Gerust")]
+    Update {
+        #[arg(
+            short,
+            long,
+            value_parser,
+            help = "The folder of the project to update; defaults to the current directory"
+        )]
+        project_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            value_parser,
+            help = "A local copy of the blueprint the project was generated with; by default, it is fetched from the Gerust repository"
+        )]
+        base_blueprint: Option<PathBuf>,
+    },
+    #[command(
+        about = "Add features to an existing project, e.g. database access to a minimal project"
+    )]
+    Add {
+        #[arg(
+            value_enum,
+            value_name = "FEATURE",
+            required = true,
+            help = "The feature(s) to add"
+        )]
+        features: Vec<Feature>,
+        #[arg(
+            short,
+            long,
+            value_parser,
+            help = "The folder of the project to add the features to; defaults to the current directory"
+        )]
+        project_dir: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_enum,
+            help = "The database backend to use when adding database access; defaults to the one recorded in the project"
+        )]
+        database: Option<Database>,
+    },
+    #[command(
+        about = "Check whether an existing project still matches the structure Gerust expects"
+    )]
+    Doctor {
+        #[arg(
+            short,
+            long,
+            value_parser,
+            help = "The folder of the project to check; defaults to the current directory"
+        )]
+        project_dir: Option<PathBuf>,
+    },
+}
+
 #[doc(hidden)]
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    let mut stdout = std::io::stdout();
-    let mut stderr = std::io::stderr();
+    let (mut stdout, mut stderr): (Box<dyn Write>, Box<dyn Write>) = match cli.output {
+        Output::Human => (Box::new(io::stdout()), Box::new(io::stderr())),
+        // the report is the only output in JSON mode
+        Output::Json => (Box::new(io::sink()), Box::new(io::sink())),
+    };
     let mut ui = ui::UI::new(&mut stdout, &mut stderr, !cli.no_color, cli.debug);
 
+    match cli.command {
+        Some(Commands::Update {
+            project_dir,
+            base_blueprint,
+        }) => {
+            let project_dir = project_dir.unwrap_or_else(|| PathBuf::from("."));
+            ui.info(&format!("Updating {}…", project_dir.display()));
+            ui.indent();
+            let result = update::update(&mut ui, &project_dir, base_blueprint.as_deref());
+            ui.outdent();
+            match result {
+                Ok(report) if report.conflicts.is_empty() => {
+                    ui.success(&format!("Updated project to Gerust {VERSION}."));
+                    return Exit::Success.into();
+                }
+                Ok(report) => {
+                    ui.error(
+                        &format!(
+                            "Updated project to Gerust {VERSION} with {} conflict(s) that need to be resolved manually!",
+                            report.conflicts.len()
+                        ),
+                        &anyhow::anyhow!("merge conflicts"),
+                    );
+                    return Exit::MergeConflicts.into();
+                }
+                Err(e) => {
+                    ui.error("Could not update project!", &e);
+                    return Exit::Failure.into();
+                }
+            }
+        }
+        Some(Commands::Add {
+            features,
+            project_dir,
+            database,
+        }) => {
+            let project_dir = project_dir.unwrap_or_else(|| PathBuf::from("."));
+            let added = Features::from_iter(features.iter().copied());
+            ui.info(&format!("Adding {added} to {}…", project_dir.display()));
+            ui.indent();
+            let result = add::add(&mut ui, &project_dir, &features, database);
+            ui.outdent();
+            match result {
+                Ok(report) if report.conflicts.is_empty() => {
+                    ui.success(&format!("Added {added}."));
+                    return Exit::Success.into();
+                }
+                Ok(report) => {
+                    ui.error(
+                        &format!(
+                            "Added {added} with {} conflict(s) that need to be resolved manually!",
+                            report.conflicts.len()
+                        ),
+                        &anyhow::anyhow!("merge conflicts"),
+                    );
+                    return Exit::MergeConflicts.into();
+                }
+                Err(e) => {
+                    ui.error(&format!("Could not add {added}!"), &e);
+                    return Exit::Failure.into();
+                }
+            }
+        }
+        Some(Commands::Doctor { project_dir }) => {
+            let project_dir = project_dir.unwrap_or_else(|| PathBuf::from("."));
+            ui.info(&format!("Checking {}…", project_dir.display()));
+            ui.indent();
+            let result = doctor::doctor(&mut ui, &project_dir);
+            ui.outdent();
+            match result {
+                Ok(checks) => {
+                    let errors: usize = checks.iter().map(doctor::Check::errors).sum();
+                    let warnings: usize = checks.iter().map(doctor::Check::warnings).sum();
+                    if errors > 0 {
+                        ui.error(
+                            &format!(
+                                "Found {errors} problem(s) and {warnings} warning(s) that need to be fixed!"
+                            ),
+                            &anyhow::anyhow!("project checks failed"),
+                        );
+                        return Exit::Failure.into();
+                    } else if warnings > 0 {
+                        ui.success(&format!(
+                            "Project matches the expected structure; found {warnings} warning(s)."
+                        ));
+                    } else {
+                        ui.success("Project matches the expected structure.");
+                    }
+                    return Exit::Success.into();
+                }
+                Err(e) => {
+                    ui.error("Could not check project!", &e);
+                    return Exit::Failure.into();
+                }
+            }
+        }
+        None => {}
+    }
+
+    let Some(name) = cli.name else {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "the name of the project to generate is required",
+            )
+            .exit();
+    };
+
     let blueprint = if cli.full {
         Blueprint::Full
     } else if cli.minimal {
@@ -95,23 +356,140 @@ async fn main() {
     } else {
         Blueprint::Default
     };
+    let features = Features::build(blueprint, &cli.with, &cli.without);
 
-    ui.info(&format!("Generating {}…", cli.name));
+    if cli.dry_run {
+        ui.info(&format!("Generating {name} (dry run)…"));
+    } else {
+        ui.info(&format!("Generating {name}…"));
+    }
     ui.indent();
+    ui.log(&format!("Features: {features}"));
+    if features.contains(Feature::Db) {
+        ui.log(&format!("Database: {}", cli.database));
+    }
+    let blueprint = cli.blueprint.unwrap_or_default();
+    if blueprint != BlueprintSource::Builtin {
+        ui.log(&format!("Blueprint: {blueprint}"));
+    }
 
-    match generate(&cli.name, cli.outdir, !cli.no_vcs, blueprint) {
-        Ok(output_dir) => {
-            ui.outdent();
+    let mut report = Report::new(&name, &features, cli.database, &blueprint, cli.dry_run);
+    let result = if cli.dry_run {
+        preview(&name, cli.outdir, &features, cli.database, &blueprint)
+            .and_then(|preview| report.collect(&preview.path, &preview.project_dir))
+    } else {
+        generate(
+            &name,
+            cli.outdir,
+            !cli.no_vcs,
+            &features,
+            cli.database,
+            &blueprint,
+        )
+        .and_then(|output_dir| {
+            // cargo-generate does not initialize a repository inside of an existing one
+            report.vcs = if cli.no_vcs {
+                VcsInit::Disabled
+            } else if output_dir.join(".git").exists() {
+                VcsInit::Initialized
+            } else {
+                VcsInit::Skipped
+            };
+            if !cli.no_fmt {
+                report.formatting = format_project(&output_dir);
+            }
+            report.collect(&output_dir, &output_dir)
+        })
+    };
+
+    let exit = match &result {
+        Ok(()) if matches!(report.formatting, Formatting::Failed { .. }) => Exit::FormattingFailed,
+        Ok(()) => Exit::Success,
+        Err(e) if e.is::<DestinationExists>() => Exit::DestinationExists,
+        Err(_) => Exit::Failure,
+    };
+
+    if cli.dry_run && result.is_ok() {
+        for file in &report.files {
+            ui.log(&file.display().to_string());
+        }
+    }
+    ui.outdent();
+    let path = report.path.clone().unwrap_or_default();
+    match (&result, &report.formatting) {
+        (Ok(()), _) if cli.dry_run => {
             ui.success(&format!(
-                "Generated {} at {}.",
-                cli.name,
-                output_dir.display()
+                "Would generate {name} at {} ({} files).",
+                path.display(),
+                report.files.len()
             ));
         }
-        Err(e) => {
-            ui.outdent();
-            ui.error("Could not generate project!", &e);
+        (Ok(()), Formatting::Failed { message }) => {
+            ui.error(
+                &format!(
+                    "Generated {name} at {} but could not format it!",
+                    path.display()
+                ),
+                &anyhow::anyhow!("{message}"),
+            );
         }
+        (Ok(()), _) => {
+            ui.success(&format!("Generated {name} at {}.", path.display()));
+        }
+        (Err(e), _) if exit == Exit::DestinationExists => {
+            ui.error(&format!("Could not generate project: {e}"), e);
+        }
+        (Err(e), _) => {
+            ui.error("Could not generate project!", e);
+        }
+    }
+
+    if cli.output == Output::Json {
+        report.finish(exit, result.as_ref().err());
+        println!("{}", report.to_json());
+    }
+
+    exit.into()
+}
+
+/// The error returned when the directory the project would be generated in exists already.
+#[doc(hidden)]
+#[derive(Debug)]
+struct DestinationExists(PathBuf);
+
+impl std::fmt::Display for DestinationExists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, r#"The directory "{}" exists already!"#, self.0.display())
+    }
+}
+
+impl std::error::Error for DestinationExists {}
+
+#[doc(hidden)]
+fn output_dir_or_current(output_dir: Option<PathBuf>) -> Result<PathBuf, anyhow::Error> {
+    if let Some(output_dir) = output_dir {
+        Ok(output_dir)
+    } else {
+        env::current_dir().context("Failed to determine the current directory")
+    }
+}
+
+#[doc(hidden)]
+fn ensure_destination_available(name: &str, output_dir: &Path) -> Result<(), anyhow::Error> {
+    let project_dir = output_dir.join(project_dir_name(name));
+    if project_dir.exists() {
+        return Err(DestinationExists(project_dir).into());
+    }
+    Ok(())
+}
+
+/// The name of the directory cargo-generate generates a project with the given name in – names that are neither snake nor kebab case are converted to kebab case, e.g. `My App` to `my-app`.
+#[doc(hidden)]
+fn project_dir_name(name: &str) -> String {
+    if name.to_snake_case() == name {
+        String::from(name)
+    } else {
+        name.to_kebab_case()
     }
 }
 
@@ -120,41 +498,132 @@ fn generate(
     name: &str,
     output_dir: Option<PathBuf>,
     init_git_vcs: bool,
-    blueprint: Blueprint,
+    features: &Features,
+    database: Database,
+    blueprint: &BlueprintSource,
 ) -> Result<PathBuf, anyhow::Error> {
-    let output_dir = if let Some(output_dir) = output_dir {
-        output_dir
-    } else {
-        env::current_dir()?
-    };
+    let output_dir = output_dir_or_current(output_dir)?;
+    ensure_destination_available(name, &output_dir)?;
 
-    let defines: Vec<String> = vec![
-        format!("template_type={blueprint}"),
-        format!("gerust_version={VERSION}",),
-    ];
+    let blueprint = blueprint.resolve()?;
+
+    render(
+        name,
+        &output_dir,
+        init_git_vcs,
+        features,
+        database,
+        VERSION,
+        blueprint.template_path.clone(),
+    )
+}
+
+/// A project rendered into a temporary directory for `--dry-run` instead of its actual destination.
+#[doc(hidden)]
+struct Preview {
+    /// Where the project would be generated
+    path: PathBuf,
+    /// Where the project was actually rendered
+    project_dir: PathBuf,
+    _temp_dir: TempDir,
+}
+
+#[doc(hidden)]
+fn preview(
+    name: &str,
+    output_dir: Option<PathBuf>,
+    features: &Features,
+    database: Database,
+    blueprint: &BlueprintSource,
+) -> Result<Preview, anyhow::Error> {
+    let output_dir = output_dir_or_current(output_dir)?;
+    ensure_destination_available(name, &output_dir)?;
+
+    let blueprint = blueprint.resolve()?;
 
-    let template_path = build_template_path()?;
+    let temp_dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let project_dir = render(
+        name,
+        temp_dir.path(),
+        false,
+        features,
+        database,
+        VERSION,
+        blueprint.template_path.clone(),
+    )?;
+    let path = output_dir.join(
+        project_dir
+            .file_name()
+            .context("Failed to determine the project's directory name")?,
+    );
+
+    Ok(Preview {
+        path,
+        project_dir,
+        _temp_dir: temp_dir,
+    })
+}
+
+#[doc(hidden)]
+fn render(
+    name: &str,
+    output_dir: &Path,
+    init_git_vcs: bool,
+    features: &Features,
+    database: Database,
+    gerust_version: &str,
+    template_path: TemplatePath,
+) -> Result<PathBuf, anyhow::Error> {
+    let mut defines: Vec<String> = vec![
+        format!("template_type={}", features.template_type()),
+        format!("database={database}"),
+        format!("gerust_version={gerust_version}",),
+    ];
+    defines.extend(features.defines());
 
     let generate_args = GenerateArgs {
         template_path,
-        destination: Some(output_dir.clone()),
+        destination: Some(output_dir.to_path_buf()),
         name: Some(String::from(name)),
         define: defines,
         force_git_init: init_git_vcs,
-        vcs: init_git_vcs.then_some(Vcs::Git),
+        vcs: Some(if init_git_vcs { Vcs::Git } else { Vcs::None }),
         ..Default::default()
     };
 
-    let output_dir = cargo_generate::generate(generate_args)
-        .context("failed to generate project from template")?;
+    cargo_generate::generate(generate_args).context("failed to generate project from template")
+}
 
-    // Try to format the generated code.
-    let _ = std::process::Command::new("cargo")
+#[doc(hidden)]
+fn format_project(output_dir: &Path) -> Formatting {
+    match std::process::Command::new("cargo")
         .arg("fmt")
-        .current_dir(&output_dir)
-        .status();
+        .current_dir(output_dir)
+        .output()
+    {
+        Ok(output) if output.status.success() => Formatting::Succeeded,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = match stderr.trim() {
+                "" => format!("cargo fmt exited with {}", output.status),
+                stderr => String::from(stderr),
+            };
+            Formatting::Failed { message }
+        }
+        Err(e) => Formatting::Failed {
+            message: format!("Failed to run cargo fmt: {e}"),
+        },
+    }
+}
 
-    Ok(output_dir)
+/// Reads and parses a TOML file, e.g. a Cargo manifest.
+#[doc(hidden)]
+fn read_toml(path: &Path) -> Result<toml::Table, anyhow::Error> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!(r#"Could not read file "{}"!"#, path.display()))?;
+    contents
+        .parse::<toml::Table>()
+        .with_context(|| format!(r#"Could not parse file "{}"!"#, path.display()))
 }
 
 #[doc(hidden)]
@@ -174,3 +643,16 @@ fn build_template_path() -> Result<TemplatePath, anyhow::Error> {
         ..Default::default()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::project_dir_name;
+
+    #[test]
+    fn test_project_dir_name() {
+        assert_eq!(project_dir_name("my-app"), "my-app");
+        assert_eq!(project_dir_name("my_app"), "my_app");
+        assert_eq!(project_dir_name("My App"), "my-app");
+        assert_eq!(project_dir_name("MyApp"), "my-app");
+    }
+}
@@ -0,0 +1,320 @@
+use crate::features::{Database, Feature, Features};
+use crate::read_toml;
+use crate::source::BlueprintSource;
+use anyhow::Context;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use walkdir::WalkDir;
+
+/// The format in which the outcome of generating a project is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Output {
+    /// Human-readable progress messages
+    #[default]
+    Human,
+    /// A single JSON report printed to stdout once generation is complete
+    Json,
+}
+
+/// The exit codes of the `gerust` command.
+///
+/// Exit code 2 is not used here as it is what clap exits with for invalid arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exit {
+    Success,
+    Failure,
+    DestinationExists,
+    FormattingFailed,
+    MergeConflicts,
+}
+
+impl Exit {
+    pub fn code(self) -> u8 {
+        match self {
+            Exit::Success => 0,
+            Exit::Failure => 1,
+            Exit::DestinationExists => 3,
+            Exit::FormattingFailed => 4,
+            Exit::MergeConflicts => 5,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit.code())
+    }
+}
+
+/// The outcome of running `cargo fmt` on a generated project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Formatting {
+    Skipped,
+    Succeeded,
+    Failed { message: String },
+}
+
+/// The outcome of initializing a VCS repository for a generated project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum VcsInit {
+    Skipped,
+    Disabled,
+    Initialized,
+}
+
+/// The options the blueprint was rendered with.
+#[derive(Debug, Serialize)]
+pub struct Options {
+    pub blueprint: String,
+    pub template_type: String,
+    pub features: Vec<&'static str>,
+    pub database: Option<&'static str>,
+}
+
+/// A crate in the generated project's Cargo workspace.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Crate {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// The machine-readable report of generating a project that `gerust --output json` prints.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub status: Exit,
+    pub exit_code: u8,
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub dry_run: bool,
+    pub options: Options,
+    pub crates: Vec<Crate>,
+    pub files: Vec<PathBuf>,
+    pub formatting: Formatting,
+    pub vcs: VcsInit,
+    pub error: Option<String>,
+}
+
+impl Report {
+    pub fn new(
+        name: &str,
+        features: &Features,
+        database: Database,
+        blueprint: &BlueprintSource,
+        dry_run: bool,
+    ) -> Report {
+        Report {
+            status: Exit::Success,
+            exit_code: Exit::Success.code(),
+            name: String::from(name),
+            path: None,
+            dry_run,
+            options: Options {
+                blueprint: blueprint.to_string(),
+                template_type: features.template_type(),
+                features: Feature::ALL
+                    .iter()
+                    .filter(|feature| features.contains(**feature))
+                    .map(|feature| feature.name())
+                    .collect(),
+                database: features.contains(Feature::Db).then(|| database.name()),
+            },
+            crates: vec![],
+            files: vec![],
+            formatting: Formatting::Skipped,
+            vcs: VcsInit::Skipped,
+            error: None,
+        }
+    }
+
+    /// Records the files and crates of the project rendered into `rendered_dir` which is (or for dry runs would be) located at `path`.
+    pub fn collect(&mut self, path: &Path, rendered_dir: &Path) -> Result<(), anyhow::Error> {
+        self.path = Some(path.to_path_buf());
+        self.files = list_files(rendered_dir)?;
+        self.crates = list_crates(rendered_dir)?;
+        Ok(())
+    }
+
+    pub fn finish(&mut self, exit: Exit, error: Option<&anyhow::Error>) {
+        self.status = exit;
+        self.exit_code = exit.code();
+        self.error = error.map(|e| format!("{e:#}"));
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the report can always be serialized")
+    }
+}
+
+/// Lists all files in `dir` relative to it, sorted and excluding the `.git` directory.
+pub fn list_files(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut files = vec![];
+    for entry in WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+    {
+        let entry = entry.context("Failed to list the generated files")?;
+        if entry.file_type().is_file() {
+            let relative_path = entry
+                .path()
+                .strip_prefix(dir)
+                .context("Failed to list the generated files")?;
+            files.push(relative_path.to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Lists the crates of the Cargo workspace in `dir`.
+pub fn list_crates(dir: &Path) -> Result<Vec<Crate>, anyhow::Error> {
+    let manifest = read_toml(&dir.join("Cargo.toml"))?;
+    let members = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut crates = vec![];
+    for member in members.iter().filter_map(|member| member.as_str()) {
+        let manifest = read_toml(&dir.join(member).join("Cargo.toml"))?;
+        let name = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .unwrap_or(member);
+        crates.push(Crate {
+            name: String::from(name),
+            path: PathBuf::from(member),
+        });
+    }
+    Ok(crates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Blueprint;
+    use insta::assert_snapshot;
+    use std::fs;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(Exit::Success.code(), 0);
+        assert_eq!(Exit::Failure.code(), 1);
+        assert_eq!(Exit::DestinationExists.code(), 3);
+        assert_eq!(Exit::FormattingFailed.code(), 4);
+        assert_eq!(Exit::MergeConflicts.code(), 5);
+    }
+
+    #[test]
+    fn test_list_files_and_crates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"web\", \"config\"]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("web/src")).unwrap();
+        fs::write(
+            root.join("web/Cargo.toml"),
+            "[package]\nname = \"my-app-web\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("web/src/lib.rs"), "").unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/Cargo.toml"),
+            "[package]\nname = \"my-app-config\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+
+        assert_eq!(
+            list_files(root).unwrap(),
+            vec![
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("config/Cargo.toml"),
+                PathBuf::from("web/Cargo.toml"),
+                PathBuf::from("web/src/lib.rs"),
+            ]
+        );
+        assert_eq!(
+            list_crates(root).unwrap(),
+            vec![
+                Crate {
+                    name: String::from("my-app-web"),
+                    path: PathBuf::from("web"),
+                },
+                Crate {
+                    name: String::from("my-app-config"),
+                    path: PathBuf::from("config"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let features = Features::from(Blueprint::Default);
+        let mut report = Report::new(
+            "my-app",
+            &features,
+            Database::Postgres,
+            &BlueprintSource::Builtin,
+            false,
+        );
+        report.path = Some(PathBuf::from("/projects/my-app"));
+        report.crates = vec![Crate {
+            name: String::from("my-app-web"),
+            path: PathBuf::from("web"),
+        }];
+        report.files = vec![PathBuf::from("Cargo.toml")];
+        report.formatting = Formatting::Failed {
+            message: String::from("rustfmt is not installed"),
+        };
+        report.vcs = VcsInit::Initialized;
+        report.finish(Exit::FormattingFailed, None);
+
+        assert_snapshot!(report.to_json(), @r#"
+        {
+          "status": "formatting_failed",
+          "exit_code": 4,
+          "name": "my-app",
+          "path": "/projects/my-app",
+          "dry_run": false,
+          "options": {
+            "blueprint": "builtin",
+            "template_type": "default",
+            "features": [
+              "db"
+            ],
+            "database": "postgres"
+          },
+          "crates": [
+            {
+              "name": "my-app-web",
+              "path": "web"
+            }
+          ],
+          "files": [
+            "Cargo.toml"
+          ],
+          "formatting": {
+            "status": "failed",
+            "message": "rustfmt is not installed"
+          },
+          "vcs": {
+            "status": "initialized"
+          },
+          "error": null
+        }
+        "#);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{build_template_path, read_toml};
+use anyhow::{Context, bail};
+use cargo_generate::TemplatePath;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// The placeholders that [`crate::render`] defines and every blueprint must therefore declare.
+pub const REQUIRED_PLACEHOLDERS: [&str; 2] = ["template_type", "gerust_version"];
+
+/// Where the blueprint that a project is generated from comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum BlueprintSource {
+    /// The blueprint that is compiled into Gerust.
+    #[default]
+    Builtin,
+    /// A blueprint in a local directory.
+    Path(PathBuf),
+    /// A blueprint in a git repository, e.g. an organization's fork of Gerust's blueprint.
+    Git(String),
+}
+
+impl std::str::FromStr for BlueprintSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            bail!("The blueprint source must not be empty!");
+        }
+        if s.contains("://") || s.starts_with("git@") {
+            Ok(BlueprintSource::Git(String::from(s)))
+        } else {
+            Ok(BlueprintSource::Path(PathBuf::from(s)))
+        }
+    }
+}
+
+impl std::fmt::Display for BlueprintSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlueprintSource::Builtin => write!(f, "builtin"),
+            BlueprintSource::Path(path) => write!(f, "{}", path.display()),
+            BlueprintSource::Git(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+/// A blueprint that is available locally and can be rendered.
+#[derive(Debug)]
+pub struct ResolvedBlueprint {
+    pub template_path: TemplatePath,
+    /// The copy of custom blueprints; removed once the blueprint is dropped.
+    _copy: Option<TempDir>,
+}
+
+impl BlueprintSource {
+    /// Makes the blueprint available locally and validates that it declares all [`REQUIRED_PLACEHOLDERS`].
+    ///
+    /// Blueprints from git repositories are cloned into a temporary directory. Local directories that contain a bare git repository are cloned the same way. For both, the blueprint may either be located at the root of the repository or in a `blueprint` subfolder, as it is in forks of Gerust's repository.
+    ///
+    /// Custom blueprints are copied to a temporary directory with all symlinks resolved before they are rendered – Gerust's blueprint links to files outside of it, e.g. the `cli` crate's `ui` module, which would otherwise end up as broken links in the generated project.
+    pub fn resolve(&self) -> Result<ResolvedBlueprint, anyhow::Error> {
+        match self {
+            BlueprintSource::Builtin => Ok(ResolvedBlueprint {
+                template_path: build_template_path()?,
+                _copy: None,
+            }),
+            BlueprintSource::Path(path) if is_bare_repository(path) => {
+                let url = path
+                    .to_str()
+                    .context("Invalid path to the blueprint repository!")?;
+                resolve_git(url)
+            }
+            BlueprintSource::Path(path) => {
+                if !path.is_dir() {
+                    bail!(
+                        r#"The blueprint directory "{}" does not exist!"#,
+                        path.display()
+                    );
+                }
+                let blueprint_dir = find_blueprint_dir(path)?;
+                validate(&blueprint_dir)?;
+                copy_blueprint(&blueprint_dir)
+            }
+            BlueprintSource::Git(url) => resolve_git(url),
+        }
+    }
+}
+
+fn resolve_git(url: &str) -> Result<ResolvedBlueprint, anyhow::Error> {
+    let checkout = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    clone_repository(url, checkout.path())
+        .with_context(|| format!(r#"Could not clone the blueprint repository "{url}"!"#))?;
+    let blueprint_dir = find_blueprint_dir(checkout.path())
+        .and_then(|blueprint_dir| validate(&blueprint_dir).map(|_| blueprint_dir))
+        .with_context(|| format!(r#"The blueprint repository "{url}" is invalid!"#))?;
+    copy_blueprint(&blueprint_dir)
+}
+
+/// Clones the repository at `url` into `path`, authenticating via the SSH agent for SSH URLs like `git@github.com:my-org/gerust.git`.
+fn clone_repository(url: &str, path: &Path) -> Result<git2::Repository, git2::Error> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    let mut tried_agent = false;
+    callbacks.credentials(move |_url, username, allowed_types| {
+        // libgit2 keeps asking for credentials as long as authentication fails so the agent is only asked once
+        if allowed_types.contains(git2::CredentialType::SSH_KEY) && !tried_agent {
+            tried_agent = true;
+            git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else {
+            Err(git2::Error::from_str(
+                "Authentication failed – repositories are authenticated via the SSH agent, make sure it is running and has a key for the repository added!",
+            ))
+        }
+    });
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+
+    git2::build::RepoBuilder::new()
+        .fetch_options(fetch_options)
+        .clone(url, path)
+}
+
+/// Copies the blueprint in `dir` to a temporary directory, replacing symlinks with the files they point to.
+fn copy_blueprint(dir: &Path) -> Result<ResolvedBlueprint, anyhow::Error> {
+    let copy = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    for entry in WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+    {
+        let entry = entry.context("Failed to copy the blueprint")?;
+        let relative_path = entry
+            .path()
+            .strip_prefix(dir)
+            .context("Failed to copy the blueprint")?;
+        let target = copy.path().join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).with_context(|| {
+                format!(r#"Could not create directory "{}"!"#, target.display())
+            })?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!(r#"Could not write file "{}"!"#, target.display()))?;
+        }
+    }
+
+    let path = copy
+        .path()
+        .to_str()
+        .context("Invalid path to the blueprint!")?;
+    Ok(ResolvedBlueprint {
+        template_path: TemplatePath {
+            path: Some(String::from(path)),
+            ..Default::default()
+        },
+        _copy: Some(copy),
+    })
+}
+
+fn is_bare_repository(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+/// Finds the directory that contains the blueprint's `cargo-generate.toml` – either `dir` itself or its `blueprint` subfolder.
+fn find_blueprint_dir(dir: &Path) -> Result<PathBuf, anyhow::Error> {
+    [dir.to_path_buf(), dir.join("blueprint")]
+        .into_iter()
+        .find(|dir| dir.join("cargo-generate.toml").is_file())
+        .with_context(|| {
+            format!(
+                r#"Could not find a blueprint in "{}" – neither it nor its blueprint subfolder contain a cargo-generate.toml!"#,
+                dir.display()
+            )
+        })
+}
+
+/// Validates that the blueprint in `dir` declares all [`REQUIRED_PLACEHOLDERS`].
+pub fn validate(dir: &Path) -> Result<(), anyhow::Error> {
+    let path = dir.join("cargo-generate.toml");
+    let config = read_toml(&path)?;
+    let placeholders = config.get("placeholders").and_then(toml::Value::as_table);
+
+    let missing: Vec<&str> = REQUIRED_PLACEHOLDERS
+        .into_iter()
+        .filter(|placeholder| {
+            !placeholders.is_some_and(|placeholders| placeholders.contains_key(*placeholder))
+        })
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            r#"The blueprint in "{}" does not declare the placeholder(s) {} that Gerust requires!"#,
+            dir.display(),
+            missing.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[placeholders]
+template_type = { type = "string", prompt = "What template do you want to use?" }
+gerust_version = { type = "string", prompt = "Which version of Gerust is the project generated with?" }
+"#;
+
+    fn commit_all(repo: &git2::Repository) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Gerust", "gerust@example.com").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "blueprint",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "../my-blueprint".parse::<BlueprintSource>().unwrap(),
+            BlueprintSource::Path(PathBuf::from("../my-blueprint"))
+        );
+        assert_eq!(
+            "https://github.com/my-org/gerust.git"
+                .parse::<BlueprintSource>()
+                .unwrap(),
+            BlueprintSource::Git(String::from("https://github.com/my-org/gerust.git"))
+        );
+        assert_eq!(
+            "git@github.com:my-org/gerust.git"
+                .parse::<BlueprintSource>()
+                .unwrap(),
+            BlueprintSource::Git(String::from("git@github.com:my-org/gerust.git"))
+        );
+        assert!("".parse::<BlueprintSource>().is_err());
+    }
+
+    #[test]
+    fn test_builtin_blueprint_is_valid() {
+        validate(&Path::new(env!("CARGO_MANIFEST_DIR")).join("blueprint")).unwrap();
+    }
+
+    #[test]
+    fn test_validate_missing_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("cargo-generate.toml"),
+            "[placeholders]\ntemplate_type = { type = \"string\", prompt = \"?\" }\n",
+        )
+        .unwrap();
+
+        let error = validate(dir.path()).unwrap_err();
+        assert!(
+            error.to_string().ends_with(
+                "does not declare the placeholder(s) gerust_version that Gerust requires!"
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_path_with_blueprint_subfolder() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("blueprint")).unwrap();
+        fs::write(dir.path().join("blueprint/cargo-generate.toml"), CONFIG).unwrap();
+        fs::write(dir.path().join("ui.rs"), "pub struct UI;").unwrap();
+        std::os::unix::fs::symlink("../ui.rs", dir.path().join("blueprint/ui.rs")).unwrap();
+
+        let resolved = BlueprintSource::Path(dir.path().to_path_buf())
+            .resolve()
+            .unwrap();
+        let copy = PathBuf::from(resolved.template_path.path.clone().unwrap());
+        assert!(copy.join("cargo-generate.toml").is_file());
+        assert!(!copy.join("ui.rs").is_symlink());
+        assert_eq!(
+            fs::read_to_string(copy.join("ui.rs")).unwrap(),
+            "pub struct UI;"
+        );
+    }
+
+    #[test]
+    fn test_resolve_bare_repository() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(work_dir.path()).unwrap();
+        fs::write(work_dir.path().join("cargo-generate.toml"), CONFIG).unwrap();
+        fs::write(work_dir.path().join("README.md"), "# {{project-name}}").unwrap();
+        commit_all(&repo);
+
+        let bare_dir = tempfile::tempdir().unwrap();
+        let bare_repo_dir = bare_dir.path().join("blueprint.git");
+        git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(work_dir.path().to_str().unwrap(), &bare_repo_dir)
+            .unwrap();
+
+        let resolved = BlueprintSource::Path(bare_repo_dir).resolve().unwrap();
+        let checkout = PathBuf::from(resolved.template_path.path.clone().unwrap());
+        assert!(checkout.join("README.md").is_file());
+    }
+
+    #[test]
+    fn test_resolve_invalid_repository() {
+        let work_dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(work_dir.path()).unwrap();
+        fs::write(
+            work_dir.path().join("cargo-generate.toml"),
+            "[template]\ncargo_generate_version = \">=0.18.0\"\n",
+        )
+        .unwrap();
+        commit_all(&repo);
+
+        let url = format!("file://{}", work_dir.path().display());
+        let error = BlueprintSource::Git(url).resolve().unwrap_err();
+        assert!(
+            format!("{error:#}").ends_with(
+                "does not declare the placeholder(s) template_type, gerust_version that Gerust requires!"
+            )
+        );
+    }
+}
@@ -9,6 +9,7 @@ pub struct UI<'a> {
     log_prefix: String,
     info_prefix: String,
     success_prefix: String,
+    warning_prefix: String,
     error_prefix: String,
 }
 
@@ -39,6 +40,11 @@ impl<'a> UI<'a> {
         } else {
             String::new()
         };
+        let warning_prefix = if color {
+            String::from("⚠️  ")
+        } else {
+            String::new()
+        };
         let error_prefix = if color {
             String::from("❌ ")
         } else {
@@ -58,6 +64,7 @@ impl<'a> UI<'a> {
             log_prefix,
             info_prefix,
             success_prefix,
+            warning_prefix,
             error_prefix,
         }
     }
@@ -88,6 +95,14 @@ impl<'a> UI<'a> {
         self.out(&format!("{}{}{}", indentation, self.success_prefix, msg));
     }
 
+    /// Prints a warning message.
+    ///
+    /// If color output is enabled (see [`UI::new`]), the message will be formatted.
+    pub fn warning(&mut self, msg: &str) {
+        let indentation = self.indentation();
+        self.errout(&format!("{}{}{}", indentation, self.warning_prefix, msg));
+    }
+
     /// Prints an error message.
     ///
     /// If color output is enabled (see [`UI::new`]), the message will be formatted. If debug output is enabled (see [`UI::new`]), the error's stack trace will be printed as well.
@@ -169,6 +184,7 @@ mod tests {
         ui.log("a general message");
         ui.info("an info message");
         ui.success("a success message ✓");
+        ui.warning("a warning message");
         ui.error("an error message :(", &anyhow!("oh no…"));
 
         let output = read_buffer(stdout);
@@ -179,7 +195,10 @@ mod tests {
         an info message
         a success message ✓
         ");
-        assert_snapshot!(error_output, @"an error message :(");
+        assert_snapshot!(error_output, @"
+        a warning message
+        an error message :(
+        ");
     }
 
     #[test]
@@ -190,6 +209,7 @@ mod tests {
         ui.log("a general message");
         ui.info("an info message");
         ui.success("a success message ✓");
+        ui.warning("a warning message");
         ui.error("an error message :(", &anyhow!("oh no…"));
 
         let output = read_buffer(stdout);
@@ -200,7 +220,10 @@ mod tests {
         ℹ️  an info message
         ✅ a success message ✓
         ");
-        assert_snapshot!(error_output, @"❌ an error message :(");
+        assert_snapshot!(error_output, @"
+        ⚠️  a warning message
+        ❌ an error message :(
+        ");
     }
 
     #[test]
@@ -0,0 +1,500 @@
+use crate::features::{Database, Feature, Features};
+use crate::ui::UI;
+use crate::{Blueprint, VERSION, build_template_path, format_project, read_toml, render};
+use anyhow::{Context, anyhow};
+use cargo_generate::TemplatePath;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// The repository that blueprints of previous Gerust versions are fetched from.
+const GERUST_REPOSITORY: &str = "https://github.com/mainmatter/gerust.git";
+
+/// The suffix of files that conflicting merge results are written to.
+const CONFLICT_SUFFIX: &str = "gerust-update";
+
+/// The settings a project was generated with.
+#[derive(Debug)]
+pub struct ProjectSettings {
+    /// The name of the project as passed to `gerust` when generating it.
+    pub project_name: String,
+    /// The features the project was generated with.
+    pub features: Features,
+    /// The database backend the project uses (or would use if it does not include database access).
+    pub database: Database,
+    /// The version of Gerust the project was generated with, e.g. `0.0.7 (b3ea9c7…)`.
+    pub generated_with: String,
+}
+
+/// The result of updating a project, listing all affected files relative to the project root.
+#[derive(Debug, Default)]
+pub struct UpdateReport {
+    /// Files that were changed by the blueprint and merged into the project cleanly.
+    pub updated: Vec<PathBuf>,
+    /// Files that were added to the blueprint and did not exist in the project yet.
+    pub added: Vec<PathBuf>,
+    /// Files that could not be merged cleanly; these were left unchanged.
+    pub conflicts: Vec<PathBuf>,
+    /// Files that were removed from the blueprint and were deleted from the project as well.
+    pub removed: Vec<PathBuf>,
+    /// Files that were changed or removed by the blueprint but have been deleted or changed in the project, respectively.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// The outcome of a three-way merge of a single file.
+#[derive(Debug, PartialEq)]
+enum Merge<T = String> {
+    /// The project's version of the file is up-to-date already.
+    Unchanged,
+    /// The blueprint's changes were merged into the project's version of the file cleanly.
+    Merged(T),
+    /// The blueprint's changes conflict with local changes; contains the merge result with conflict markers (or the blueprint's version for files that are not text).
+    Conflict(T),
+}
+
+/// Updates the project in `project_dir` to the blueprint of the running version of Gerust.
+///
+/// The blueprint is rendered twice – once for the version of Gerust the project was generated with (the base) and once for the running version. Changes between the two are then merged into the project's files with a three-way merge so that local changes are preserved. Files that cannot be merged cleanly are left unchanged; the merge result including conflict markers is written next to them instead.
+pub fn update(
+    ui: &mut UI,
+    project_dir: &Path,
+    base_blueprint: Option<&Path>,
+) -> Result<UpdateReport, anyhow::Error> {
+    let settings = read_project_settings(project_dir)?;
+    if settings.generated_with == VERSION {
+        ui.log(&format!(
+            "Project is up-to-date with Gerust {VERSION} already."
+        ));
+        return Ok(UpdateReport::default());
+    }
+
+    ui.log(&format!(
+        "Rendering blueprint for Gerust {}…",
+        settings.generated_with
+    ));
+    let base_dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let base_template_path = build_base_template_path(&settings, base_blueprint)?;
+    let base_project_dir = render(
+        &settings.project_name,
+        base_dir.path(),
+        false,
+        &settings.features,
+        settings.database,
+        &settings.generated_with,
+        base_template_path,
+    )
+    .context("Failed to render the blueprint the project was generated with")?;
+    format_project(&base_project_dir);
+
+    ui.log(&format!("Rendering blueprint for Gerust {VERSION}…"));
+    let new_dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let new_project_dir = render(
+        &settings.project_name,
+        new_dir.path(),
+        false,
+        &settings.features,
+        settings.database,
+        VERSION,
+        build_template_path()?,
+    )
+    .context("Failed to render the current blueprint")?;
+    format_project(&new_project_dir);
+
+    merge_into_project(ui, &base_project_dir, &new_project_dir, project_dir)
+}
+
+/// Reads the settings a project was generated with from its workspace's `Cargo.toml`.
+///
+/// Projects generated with versions of Gerust that did not record the settings in the workspace metadata yet are supported as well – the settings are inferred from the `cli` crate's package metadata and the project's structure in that case.
+pub fn read_project_settings(project_dir: &Path) -> Result<ProjectSettings, anyhow::Error> {
+    let manifest = read_toml(&project_dir.join("Cargo.toml"))?;
+    let metadata = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("metadata"))
+        .and_then(|metadata| metadata.get("gerust"));
+
+    if let Some(metadata) = metadata {
+        let get = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+                .with_context(|| format!("Missing {key} in [workspace.metadata.gerust]!"))
+        };
+
+        let features = match metadata.get("features") {
+            Some(features) => Feature::ALL
+                .into_iter()
+                .filter(|feature| {
+                    features
+                        .get(feature.name())
+                        .and_then(toml::Value::as_bool)
+                        .unwrap_or_default()
+                })
+                .collect(),
+            None => Features::from(get("template_type")?.parse::<Blueprint>()?),
+        };
+
+        let database = match metadata.get("database") {
+            Some(_) => get("database")?.parse()?,
+            None => Database::default(),
+        };
+
+        return Ok(ProjectSettings {
+            project_name: get("project_name")?,
+            features,
+            database,
+            generated_with: get("generated_with")?,
+        });
+    }
+
+    let cli_manifest = read_toml(&project_dir.join("cli").join("Cargo.toml"))
+        .context("Not a Gerust project: could not find the cli crate!")?;
+    let package = cli_manifest
+        .get("package")
+        .context("Missing [package] in cli/Cargo.toml!")?;
+    let project_name = package
+        .get("name")
+        .and_then(toml::Value::as_str)
+        .and_then(|name| name.strip_suffix("-cli"))
+        .context("Could not determine the project name from cli/Cargo.toml!")?;
+    let generated_with = package
+        .get("metadata")
+        .and_then(|metadata| metadata.get("gerust"))
+        .and_then(|gerust| gerust.get("generated_with"))
+        .and_then(toml::Value::as_str)
+        .context("Missing generated_with in [package.metadata.gerust] in cli/Cargo.toml!")?;
+    let blueprint = if !project_dir.join("db").exists() {
+        Blueprint::Minimal
+    } else if project_dir.join("web/src/middlewares/auth.rs").exists() {
+        Blueprint::Full
+    } else {
+        Blueprint::Default
+    };
+
+    Ok(ProjectSettings {
+        project_name: String::from(project_name),
+        features: Features::from(blueprint),
+        database: Database::default(),
+        generated_with: String::from(generated_with),
+    })
+}
+
+/// Builds the template path for the blueprint of the version of Gerust a project was generated with.
+///
+/// Unless a local copy of that blueprint is passed, it is fetched from the Gerust repository at the revision recorded in the project's settings.
+fn build_base_template_path(
+    settings: &ProjectSettings,
+    base_blueprint: Option<&Path>,
+) -> Result<TemplatePath, anyhow::Error> {
+    if let Some(base_blueprint) = base_blueprint {
+        let path = base_blueprint
+            .to_str()
+            .context("Invalid path to the base blueprint!")?;
+        return Ok(TemplatePath {
+            path: Some(String::from(path)),
+            ..Default::default()
+        });
+    }
+
+    let revision = settings
+        .generated_with
+        .split_once('(')
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+        .filter(|revision| !revision.is_empty())
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not determine the revision of Gerust {}; pass the blueprint the project was generated with via --base-blueprint!",
+                settings.generated_with
+            )
+        })?;
+
+    Ok(TemplatePath {
+        git: Some(String::from(GERUST_REPOSITORY)),
+        revision: Some(String::from(revision)),
+        subfolder: Some(String::from("blueprint")),
+        ..Default::default()
+    })
+}
+
+/// Merges the changes between the rendered blueprints in `base_dir` and `new_dir` into the project in `project_dir`.
+pub fn merge_into_project(
+    ui: &mut UI,
+    base_dir: &Path,
+    new_dir: &Path,
+    project_dir: &Path,
+) -> Result<UpdateReport, anyhow::Error> {
+    let mut report = UpdateReport::default();
+
+    let entries = WalkDir::new(new_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !is_ignored(entry.path()));
+    for entry in entries {
+        let entry = entry.context("Failed to read the rendered blueprint")?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(new_dir)
+            .expect("Rendered files are located inside of the rendered project")
+            .to_path_buf();
+        let project_path = project_dir.join(&relative_path);
+        let new = fs::read(entry.path())
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+        let base = fs::read(base_dir.join(&relative_path)).ok();
+        let ours = fs::read(&project_path).ok();
+
+        match (base, ours) {
+            (Some(_), None) => {
+                ui.log(&format!(
+                    "Skipped {} as it has been deleted from the project.",
+                    relative_path.display()
+                ));
+                report.skipped.push(relative_path);
+            }
+            (None, None) => {
+                write_project_file(&project_path, &new)?;
+                ui.log(&format!("Added {}.", relative_path.display()));
+                report.added.push(relative_path);
+            }
+            (base, Some(ours)) => match merge_files(base.as_deref(), &ours, &new) {
+                Merge::Unchanged => {}
+                Merge::Merged(merged) => {
+                    write_project_file(&project_path, &merged)?;
+                    ui.log(&format!("Updated {}.", relative_path.display()));
+                    report.updated.push(relative_path);
+                }
+                Merge::Conflict(conflicted) => {
+                    let mut conflict_path = project_path.into_os_string();
+                    conflict_path.push(format!(".{CONFLICT_SUFFIX}"));
+                    let conflict_path = PathBuf::from(conflict_path);
+                    write_project_file(&conflict_path, &conflicted)?;
+                    if is_text(&ours) && is_text(&new) {
+                        ui.log(&format!(
+                            "Conflict in {}; the merge result with conflict markers was written to {}.",
+                            relative_path.display(),
+                            conflict_path.display()
+                        ));
+                    } else {
+                        ui.log(&format!(
+                            "Conflict in {}; as it is not a text file, the blueprint's version was written to {}.",
+                            relative_path.display(),
+                            conflict_path.display()
+                        ));
+                    }
+                    report.conflicts.push(relative_path);
+                }
+            },
+        }
+    }
+
+    let entries = WalkDir::new(base_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !is_ignored(entry.path()));
+    for entry in entries {
+        let entry = entry.context("Failed to read the rendered blueprint")?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(base_dir)
+            .expect("Rendered files are located inside of the rendered project")
+            .to_path_buf();
+        if new_dir.join(&relative_path).exists() {
+            continue;
+        }
+
+        let project_path = project_dir.join(&relative_path);
+        let Ok(ours) = fs::read(&project_path) else {
+            continue;
+        };
+        let base = fs::read(entry.path())
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+
+        if ours == base {
+            remove_project_file(project_dir, &project_path)?;
+            ui.log(&format!("Removed {}.", relative_path.display()));
+            report.removed.push(relative_path);
+        } else {
+            ui.log(&format!(
+                "Kept {} as it has local changes although it was removed from the blueprint.",
+                relative_path.display()
+            ));
+            report.skipped.push(relative_path);
+        }
+    }
+
+    Ok(report)
+}
+
+fn is_ignored(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == ".git" || name == "target")
+}
+
+fn write_project_file(path: &Path, contents: &[u8]) -> Result<(), anyhow::Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!(r#"Could not create directory "{}""#, parent.display()))?;
+    }
+    fs::write(path, contents)
+        .with_context(|| format!(r#"Could not write file "{}""#, path.display()))
+}
+
+fn remove_project_file(project_dir: &Path, path: &Path) -> Result<(), anyhow::Error> {
+    fs::remove_file(path)
+        .with_context(|| format!(r#"Could not remove file "{}""#, path.display()))?;
+
+    // Clean up directories that are left empty, e.g. a migration's directory.
+    let mut parent = path.parent();
+    while let Some(dir) = parent.filter(|dir| *dir != project_dir) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+        parent = dir.parent();
+    }
+
+    Ok(())
+}
+
+/// Merges the changes between `base` and `theirs` into `ours` like [`merge`] if all versions of a file are text.
+///
+/// Files that are not text, e.g. images, cannot be merged line by line: they are replaced with the blueprint's version if they do not have local changes and conflict otherwise.
+fn merge_files(base: Option<&[u8]>, ours: &[u8], theirs: &[u8]) -> Merge<Vec<u8>> {
+    if let (Ok(base), Ok(ours), Ok(theirs)) = (
+        base.map(std::str::from_utf8).transpose(),
+        std::str::from_utf8(ours),
+        std::str::from_utf8(theirs),
+    ) {
+        return match merge(base, ours, theirs) {
+            Merge::Unchanged => Merge::Unchanged,
+            Merge::Merged(merged) => Merge::Merged(merged.into_bytes()),
+            Merge::Conflict(conflicted) => Merge::Conflict(conflicted.into_bytes()),
+        };
+    }
+
+    if ours == theirs {
+        Merge::Unchanged
+    } else if base == Some(ours) {
+        Merge::Merged(theirs.to_vec())
+    } else {
+        Merge::Conflict(theirs.to_vec())
+    }
+}
+
+fn is_text(contents: &[u8]) -> bool {
+    std::str::from_utf8(contents).is_ok()
+}
+
+/// Merges the changes between `base` and `theirs` into `ours`.
+///
+/// `base` is the file as rendered by the blueprint the project was generated with (if it existed in that blueprint), `ours` is the project's version of the file, and `theirs` is the file as rendered by the current blueprint.
+fn merge(base: Option<&str>, ours: &str, theirs: &str) -> Merge {
+    if ours == theirs {
+        return Merge::Unchanged;
+    }
+    if base == Some(ours) {
+        return Merge::Merged(String::from(theirs));
+    }
+
+    match diffy::merge(base.unwrap_or_default(), ours, theirs) {
+        Ok(merged) if merged == ours => Merge::Unchanged,
+        Ok(merged) => Merge::Merged(merged),
+        Err(conflicted) => Merge::Conflict(conflicted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Merge, merge, merge_files};
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_merge_unchanged() {
+        assert_eq!(merge(Some("a\n"), "b\n", "b\n"), Merge::Unchanged);
+        assert_eq!(merge(Some("a\n"), "b\n", "a\n"), Merge::Unchanged);
+    }
+
+    #[test]
+    fn test_merge_untouched_file() {
+        assert_eq!(
+            merge(Some("a\n"), "a\n", "b\n"),
+            Merge::Merged(String::from("b\n"))
+        );
+    }
+
+    #[test]
+    fn test_merge_local_changes() {
+        let base = "use a;\n\nfn main() {\n    a();\n}\n";
+        let ours = "use a;\nuse b;\n\nfn main() {\n    a();\n}\n";
+        let theirs = "use a;\n\nfn main() {\n    a();\n    c();\n}\n";
+
+        let Merge::Merged(merged) = merge(Some(base), ours, theirs) else {
+            panic!("Expected a clean merge");
+        };
+
+        assert_snapshot!(merged, @r"
+        use a;
+        use b;
+
+        fn main() {
+            a();
+            c();
+        }
+        ");
+    }
+
+    #[test]
+    fn test_merge_files_binary() {
+        let base: &[u8] = &[0x89, 0x50, 0x4e, 0x47, 0x00];
+        let ours: &[u8] = &[0x89, 0x50, 0x4e, 0x47, 0x01];
+        let theirs: &[u8] = &[0x89, 0x50, 0x4e, 0x47, 0xff];
+
+        assert_eq!(merge_files(Some(base), ours, ours), Merge::Unchanged);
+        assert_eq!(
+            merge_files(Some(base), base, theirs),
+            Merge::Merged(theirs.to_vec())
+        );
+        assert_eq!(
+            merge_files(Some(base), ours, theirs),
+            Merge::Conflict(theirs.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_merge_files_text() {
+        assert_eq!(
+            merge_files(Some(b"a\n"), b"a\n", b"b\n"),
+            Merge::Merged(b"b\n".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let base = "fn main() {\n    a();\n}\n";
+        let ours = "fn main() {\n    b();\n}\n";
+        let theirs = "fn main() {\n    c();\n}\n";
+
+        let Merge::Conflict(conflicted) = merge(Some(base), ours, theirs) else {
+            panic!("Expected a conflict");
+        };
+
+        assert_snapshot!(conflicted, @r"
+        fn main() {
+        <<<<<<< ours
+            b();
+        ||||||| original
+            a();
+        =======
+            c();
+        >>>>>>> theirs
+        }
+        ");
+    }
+}